@@ -0,0 +1,13 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub struct DutchAuction<M: ManagedTypeApi> {
+    pub id: u64,
+    pub start_price: BigUint<M>,
+    pub floor_price: BigUint<M>,
+    pub price_step: BigUint<M>,
+    pub step_interval: u64,
+    pub start_timestamp: u64,
+    pub rebate: bool,
+}
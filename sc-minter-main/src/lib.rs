@@ -4,6 +4,8 @@ use core::convert::TryInto;
 
 const NFT_AMOUNT: u32 = 1;
 const ROYALTIES_MAX: u32 = 10_000;
+const SHARES_TOTAL: u32 = 10_000;
+const STAKING_DEPOSIT_GAS_LIMIT: u64 = 10_000_000;
 const IPFS_GATEWAY_HOST: &[u8] = "https://ipfs.io/ipfs/".as_bytes();
 const METADATA_KEY_NAME: &[u8] = "metadata:".as_bytes();
 const METADATA_FILE_EXTENSION: &[u8] = ".json".as_bytes();
@@ -16,17 +18,37 @@ const ESDT_NFT_UPDATE_ATTRIBUTES_FUNC_NAME: &[u8] = "ESDTNFTUpdateAttributes".as
 const AFTER_NAME_BEFORE_NUMBER: &[u8] = " #".as_bytes();
 const HASH_DATA_BUFFER_LEN: usize = 1024;
 
-use elrond_wasm::hex_literal::hex;
-
-// mainnet
-const MARKETPLACE: [u8; 32] =
-    hex!("00000000000000000500d3b28828d62052124f07dcd50ed31b0825f60eee1526");
-
 elrond_wasm::imports!();
 elrond_wasm::derive_imports!();
 
+use deainostri_roles::Role;
+
+pub mod timelock_action;
+use timelock_action::*;
+
+pub mod staking_proxy;
+
+pub mod dutch_auction;
+use dutch_auction::*;
+
+pub mod price_tier;
+use price_tier::*;
+
+pub mod sale_phase;
+use sale_phase::*;
+
+pub mod merkle_proof;
+use merkle_proof::*;
+
+pub mod whitelist_tier;
+use whitelist_tier::*;
+
 #[elrond_wasm::contract]
-pub trait ElvenTools {
+pub trait ElvenTools:
+    deainostri_roles::RolesModule
+    + deainostri_roles::multisig::MultisigModule
+    + deainostri_roles::timelock::TimelockModule
+{
     #[init]
     fn init(
         &self,
@@ -78,6 +100,8 @@ pub trait ElvenTools {
             let first_index = 1;
             self.next_index_to_mint().set(&first_index);
         }
+
+        self.init_timelock();
     }
 
     #[only_owner]
@@ -188,30 +212,34 @@ pub trait ElvenTools {
     // sale & pre-sale status methods
     // -----------------------
 
-    #[only_owner]
     #[endpoint(pauseMinting)]
     fn pause_minting(&self) {
+        self.require_role(Role::Operator);
+
         let paused = true;
         self.paused().set(&paused);
     }
 
-    #[only_owner]
     #[endpoint(startMinting)]
     fn start_minting(&self) {
+        self.require_role(Role::Operator);
+
         require!(!self.nft_token_id().is_empty(), "Token not issued!");
 
         self.paused().clear();
     }
 
-    #[only_owner]
     #[endpoint(pausePresale)]
     fn pause_presale(&self) {
+        self.require_role(Role::Operator);
+
         self.presale_is_open().clear();
     }
 
-    #[only_owner]
     #[endpoint(startPresale)]
     fn resume_presale(&self) {
+        self.require_role(Role::Operator);
+
         require!(!self.nft_token_id().is_empty(), "Token not issued!");
 
         self.presale_is_open().set(&true);
@@ -221,9 +249,10 @@ pub trait ElvenTools {
     // presale methods
     // -----------------------
 
-    #[only_owner]
     #[endpoint(approveWhitelist)]
     fn approve_whitelist(&self, address: ManagedAddress) {
+        self.require_role(Role::WhitelistManager);
+
         self.is_whitelisted(&address).set(&true);
     }
 
@@ -231,41 +260,21 @@ pub trait ElvenTools {
         self.is_whitelisted(&address).set(&true);
     }
 
-    #[only_owner]
     #[endpoint(approveWhitelistMany)]
-    fn approve_whitelist_many(
-        &self,
-        address_one: ManagedAddress,
-        address_two: OptionalValue<ManagedAddress>,
-        address_three: OptionalValue<ManagedAddress>,
-        address_four: OptionalValue<ManagedAddress>,
-        address_five: OptionalValue<ManagedAddress>,
-    ) {
-        //
-        self.internal_approve_whitelist(address_one);
+    fn approve_whitelist_many(&self, addresses: MultiValueEncoded<ManagedAddress>) {
+        self.require_role(Role::WhitelistManager);
 
-        if let Some(address) = address_two.into_option() {
+        for address in addresses.into_iter() {
             self.internal_approve_whitelist(address);
-        } else {
-            return;
-        }
-
-        if let Some(address) = address_three.into_option() {
-            self.internal_approve_whitelist(address);
-        } else {
-            return;
         }
+    }
 
-        if let Some(address) = address_four.into_option() {
-            self.internal_approve_whitelist(address);
-        } else {
-            return;
-        }
+    #[endpoint(removeWhitelist)]
+    fn remove_whitelist(&self, addresses: MultiValueEncoded<ManagedAddress>) {
+        self.require_role(Role::WhitelistManager);
 
-        if let Some(address) = address_five.into_option() {
-            self.internal_approve_whitelist(address);
-        } else {
-            return;
+        for address in addresses.into_iter() {
+            self.is_whitelisted(&address).clear();
         }
     }
 
@@ -273,54 +282,65 @@ pub trait ElvenTools {
     // whitelist v2
     // -----------------------
 
-    #[only_owner]
     #[endpoint(removeWhitelistPerDrop)]
     fn remove_whitelist_per_drop(&self, address: ManagedAddress) {
+        self.require_role(Role::WhitelistManager);
+
         self.is_whitelisted_per_drop(self.opened_drop().get())
             .remove(&address);
     }
 
-    #[only_owner]
     #[endpoint(approveWhitelistPerDrop)]
     fn approve_whitelist_per_drop(&self, address: ManagedAddress) {
+        self.require_role(Role::WhitelistManager);
+
         self.internal_approve_whitelist_per_drop(address);
     }
 
-    #[only_owner]
     #[endpoint(approveWhitelistPerDropMany)]
-    fn approve_whitelist_per_drop_many(
-        &self,
-        address_one: ManagedAddress,
-        address_two: OptionalValue<ManagedAddress>,
-        address_three: OptionalValue<ManagedAddress>,
-        address_four: OptionalValue<ManagedAddress>,
-        address_five: OptionalValue<ManagedAddress>,
-    ) {
-        //
-        self.internal_approve_whitelist_per_drop(address_one);
+    fn approve_whitelist_per_drop_many(&self, addresses: MultiValueEncoded<ManagedAddress>) {
+        self.require_role(Role::WhitelistManager);
 
-        if let Some(address) = address_two.into_option() {
+        for address in addresses.into_iter() {
             self.internal_approve_whitelist_per_drop(address);
-        } else {
-            return;
         }
+    }
 
-        if let Some(address) = address_three.into_option() {
-            self.internal_approve_whitelist_per_drop(address);
-        } else {
-            return;
-        }
+    #[endpoint(removeWhitelistPerDropMany)]
+    fn remove_whitelist_per_drop_many(&self, addresses: MultiValueEncoded<ManagedAddress>) {
+        self.require_role(Role::WhitelistManager);
 
-        if let Some(address) = address_four.into_option() {
-            self.internal_approve_whitelist_per_drop(address);
-        } else {
-            return;
+        let mut whitelist = self.is_whitelisted_per_drop(self.opened_drop().get());
+
+        for address in addresses.into_iter() {
+            whitelist.remove(&address);
         }
+    }
 
-        if let Some(address) = address_five.into_option() {
-            self.internal_approve_whitelist_per_drop(address);
-        } else {
-            return;
+    // copies a page of the whitelist, call again with the next offset for
+    // lists that don't fit in one transaction. The mapper has no cursor, so
+    // every page walks the `offset` entries before it: a page costs about
+    // (offset + limit) reads, and copying n addresses in pages of `limit`
+    // about n² / (2 * limit). Use the largest limit that fits the gas limit
+    // for long lists.
+    #[endpoint(copyWhitelist)]
+    fn copy_whitelist(&self, from_drop: u16, to_drop: u16, offset: usize, limit: usize) {
+        self.require_role(Role::WhitelistManager);
+
+        require!(
+            from_drop != to_drop,
+            "Can't copy a whitelist to the same drop!"
+        );
+
+        let mut to_whitelist = self.is_whitelisted_per_drop(to_drop);
+
+        for (address, _) in self
+            .is_whitelisted_per_drop(from_drop)
+            .iter()
+            .skip(offset)
+            .take(limit)
+        {
+            to_whitelist.insert(address, true);
         }
     }
 
@@ -339,6 +359,57 @@ pub trait ElvenTools {
         existing_address_value
     }
 
+    #[view(getWhitelistPerDrop)]
+    fn get_whitelist_per_drop(
+        &self,
+        drop_number: u16,
+        offset: usize,
+        limit: usize,
+    ) -> MultiValueEncoded<ManagedAddress> {
+        let mut result = MultiValueEncoded::new();
+
+        for (address, _) in self
+            .is_whitelisted_per_drop(drop_number)
+            .iter()
+            .skip(offset)
+            .take(limit)
+        {
+            result.push(address);
+        }
+
+        result
+    }
+
+    #[view(getWhitelistTiers)]
+    fn get_whitelist_tiers(
+        &self,
+        drop_id: u16,
+    ) -> MultiValueEncoded<MultiValue2<u32, WhitelistTier<Self::Api>>> {
+        let mut result = MultiValueEncoded::new();
+
+        for (tier_id, tier) in self.whitelist_tiers(drop_id).iter() {
+            result.push((tier_id, tier).into());
+        }
+
+        result
+    }
+
+    #[view(getGatedCollections)]
+    fn get_gated_collections(&self) -> MultiValueEncoded<MultiValue2<TokenIdentifier, u32>> {
+        let mut result = MultiValueEncoded::new();
+
+        for (collection, allowance) in self.gated_collections().iter() {
+            result.push((collection, allowance).into());
+        }
+
+        result
+    }
+
+    #[view(getWhitelistCountPerDrop)]
+    fn get_whitelist_count_per_drop(&self, drop_number: u16) -> usize {
+        self.is_whitelisted_per_drop(drop_number).len()
+    }
+
     #[view(getIsWhitelistedPerCurrentDrop)]
     fn get_is_whitelisted_per_current_drop(&self, address: ManagedAddress) -> bool {
         let existing_address_value =
@@ -347,25 +418,292 @@ pub trait ElvenTools {
         existing_address_value
     }
 
+    // -----------------------
+    // whitelist tier methods
+    // -----------------------
+
+    #[endpoint(setWhitelistTier)]
+    fn set_whitelist_tier(
+        &self,
+        drop_id: u16,
+        tier_id: u32,
+        price: BigUint,
+        allowance: u32,
+        start_timestamp: OptionalValue<u64>,
+    ) {
+        self.require_role(Role::Admin);
+
+        self.whitelist_tiers(drop_id).insert(
+            tier_id,
+            WhitelistTier {
+                price,
+                allowance,
+                start_timestamp: start_timestamp.into_option().unwrap_or_default(),
+            },
+        );
+    }
+
+    #[endpoint(removeWhitelistTier)]
+    fn remove_whitelist_tier(&self, drop_id: u16, tier_id: u32) {
+        self.require_role(Role::Admin);
+
+        require!(
+            self.whitelist_tiers(drop_id).remove(&tier_id).is_some(),
+            "Whitelist tier not found!"
+        );
+    }
+
+    #[endpoint(addToWhitelistTier)]
+    fn add_to_whitelist_tier(
+        &self,
+        drop_id: u16,
+        tier_id: u32,
+        addresses: MultiValueEncoded<ManagedAddress>,
+    ) {
+        self.require_role(Role::WhitelistManager);
+
+        require!(
+            self.whitelist_tiers(drop_id).contains_key(&tier_id),
+            "Whitelist tier not found!"
+        );
+
+        for address in addresses.into_iter() {
+            self.whitelist_tiers_by_address(drop_id, &address)
+                .insert(tier_id);
+        }
+    }
+
+    #[endpoint(removeFromWhitelistTier)]
+    fn remove_from_whitelist_tier(
+        &self,
+        drop_id: u16,
+        tier_id: u32,
+        addresses: MultiValueEncoded<ManagedAddress>,
+    ) {
+        self.require_role(Role::WhitelistManager);
+
+        for address in addresses.into_iter() {
+            self.whitelist_tiers_by_address(drop_id, &address)
+                .swap_remove(&tier_id);
+        }
+    }
+
+    // the cheapest started tier of the caller with enough allowance left
+    fn get_best_whitelist_tier(
+        &self,
+        address: &ManagedAddress,
+        tokens: u32,
+    ) -> Option<(u32, WhitelistTier<Self::Api>)> {
+        let drop_id = self.opened_drop().get();
+        let now = self.blockchain().get_block_timestamp();
+        let mut best_tier: Option<(u32, WhitelistTier<Self::Api>)> = None;
+
+        for tier_id in self.whitelist_tiers_by_address(drop_id, address).iter() {
+            let tier = match self.whitelist_tiers(drop_id).get(&tier_id) {
+                Some(tier) => tier,
+                None => continue,
+            };
+
+            if tier.start_timestamp > now {
+                continue;
+            }
+
+            if tier.allowance > 0 {
+                let minted = self
+                    .minted_per_whitelist_tier(drop_id, tier_id, address)
+                    .get();
+
+                if minted + tokens > tier.allowance {
+                    continue;
+                }
+            }
+
+            let is_better = match &best_tier {
+                Some((_, best)) => tier.price < best.price,
+                None => true,
+            };
+
+            if is_better {
+                best_tier = Some((tier_id, tier));
+            }
+        }
+
+        best_tier
+    }
+
+    // -----------------------
+    // token-gated presale methods
+    // -----------------------
+
+    // allowance is the number of mints each held token nonce grants
+    #[endpoint(setGatedCollection)]
+    fn set_gated_collection(&self, collection: TokenIdentifier, allowance: u32) {
+        self.require_role(Role::WhitelistManager);
+
+        require!(collection.is_valid_esdt_identifier(), "Invalid token!");
+        require!(allowance > 0, "Allowance should be greater than 0!");
+
+        self.gated_collections().insert(collection, allowance);
+    }
+
+    #[endpoint(removeGatedCollection)]
+    fn remove_gated_collection(&self, collection: TokenIdentifier) {
+        self.require_role(Role::WhitelistManager);
+
+        require!(
+            self.gated_collections().remove(&collection).is_some(),
+            "Collection is not gated!"
+        );
+    }
+
+    // -----------------------
+    // merkle whitelist methods
+    // -----------------------
+
+    #[endpoint(setMerkleRoot)]
+    fn set_merkle_root(
+        &self,
+        drop_id: u16,
+        root: ManagedByteArray<Self::Api, 32>,
+        hash: OptionalValue<MerkleHash>,
+    ) {
+        self.require_role(Role::WhitelistManager);
+
+        self.merkle_root(drop_id).set(&root);
+        self.merkle_hash(drop_id)
+            .set(&hash.into_option().unwrap_or(MerkleHash::Sha256));
+    }
+
+    #[endpoint(unsetMerkleRoot)]
+    fn unset_merkle_root(&self, drop_id: u16) {
+        self.require_role(Role::WhitelistManager);
+
+        self.merkle_root(drop_id).clear();
+        self.merkle_hash(drop_id).clear();
+    }
+
+    #[endpoint(setMerkleTierPrice)]
+    fn set_merkle_tier_price(&self, drop_id: u16, price_tier: u32, price: BigUint) {
+        self.require_role(Role::Admin);
+
+        require!(price_tier > 0, "Price tier 0 is the presale price!");
+
+        self.merkle_tier_price(drop_id, price_tier).set(&price);
+    }
+
+    fn require_valid_merkle_proof(&self, address: &ManagedAddress, proof: &MerkleProof<Self::Api>) {
+        let drop_id = self.opened_drop().get();
+        require!(
+            !self.merkle_root(drop_id).is_empty(),
+            "Merkle root is not set for this drop!"
+        );
+
+        let hash = self.merkle_hash(drop_id).get();
+
+        let mut leaf_data = address.as_managed_buffer().clone();
+        if proof.allowed_quantity > 0 || proof.price_tier > 0 {
+            leaf_data.append_bytes(&proof.allowed_quantity.to_be_bytes());
+            leaf_data.append_bytes(&proof.price_tier.to_be_bytes());
+        }
+
+        let mut node = self.merkle_hash_of(hash, &leaf_data);
+
+        // pairs are hashed in sorted order, so the path needs no directions
+        for sibling in proof.path.iter() {
+            let mut pair = ManagedBuffer::new();
+
+            if node.to_byte_array() <= sibling.to_byte_array() {
+                pair.append(node.as_managed_buffer());
+                pair.append(sibling.as_managed_buffer());
+            } else {
+                pair.append(sibling.as_managed_buffer());
+                pair.append(node.as_managed_buffer());
+            }
+
+            node = self.merkle_hash_of(hash, &pair);
+        }
+
+        require!(
+            node == self.merkle_root(drop_id).get(),
+            "Invalid merkle proof!"
+        );
+    }
+
+    fn merkle_hash_of(
+        &self,
+        hash: MerkleHash,
+        data: &ManagedBuffer,
+    ) -> ManagedByteArray<Self::Api, 32> {
+        match hash {
+            MerkleHash::Sha256 => self.crypto().sha256(data),
+            MerkleHash::Keccak256 => self.crypto().keccak256(data),
+        }
+    }
+
+    fn track_merkle_mints(
+        &self,
+        address: &ManagedAddress,
+        proof: &MerkleProof<Self::Api>,
+        tokens: u32,
+    ) {
+        let drop_id = self.opened_drop().get();
+        let minted = self.merkle_minted(drop_id, address).get() + tokens;
+
+        require!(
+            proof.allowed_quantity == 0 || minted <= proof.allowed_quantity,
+            "The whitelisted quantity is reached!"
+        );
+
+        self.merkle_minted(drop_id, address).set(&minted);
+    }
+
+    fn get_merkle_tier_price(&self, price_tier: u32) -> BigUint {
+        let tier_price = self.merkle_tier_price(self.opened_drop().get(), price_tier);
+        require!(!tier_price.is_empty(), "Price tier is not set!");
+
+        tier_price.get()
+    }
+
     // -----------------------
     // drop methods
     // -----------------------
 
-    #[only_owner]
     #[endpoint(setRoyalties)]
     fn set_royalties(&self, royalties: BigUint) {
-        self.royalties().set(&royalties);
+        self.require_role(Role::Admin);
+
+        require!(royalties <= ROYALTIES_MAX, "Royalties cannot exceed 100%!");
+
+        self.queue_action(&TimelockAction::SetRoyalties(royalties));
     }
 
-    #[only_owner]
     #[endpoint(setTotalySupply)]
     fn set_total_supply(&self, amount_of_tokens: u32) {
-        self.amount_of_tokens_total().set(&amount_of_tokens);
+        self.require_role(Role::Admin);
+
+        self.queue_action(&TimelockAction::<Self::Api>::SetTotalySupply(
+            amount_of_tokens,
+        ));
+    }
+
+    // switches minting to draw indexes at random from the unminted ones,
+    // which is only possible before the first token gets minted
+    #[endpoint(setRandomMint)]
+    fn set_random_mint(&self, enabled: bool) {
+        self.require_role(Role::Admin);
+
+        require!(
+            self.minted_indexes_total().get() == 0,
+            "Random mint can't be changed after minting started!"
+        );
+
+        self.random_mint().set(&enabled);
     }
 
-    #[only_owner]
     #[endpoint(setDrop)]
     fn set_drop(&self, amount_of_tokens_per_drop: u32) {
+        self.require_role(Role::Operator);
+
         let total_tokens_left = self.total_tokens_left();
 
         require!(
@@ -384,9 +722,10 @@ pub trait ElvenTools {
         }
     }
 
-    #[only_owner]
     #[endpoint(unsetDrop)]
     fn unset_drop(&self) {
+        self.require_role(Role::Operator);
+
         self.amount_of_tokens_per_drop().clear();
         self.minted_indexes_by_drop().clear();
         self.opened_drop().clear();
@@ -396,93 +735,714 @@ pub trait ElvenTools {
     // general set methods
     // -----------------------
 
-    #[only_owner]
     #[endpoint(setTokensLimitPerSaleTransaction)]
     fn set_tokens_limit_per_sale_transaction(&self, amount_of_tokens: u32) {
+        self.require_role(Role::Admin);
+
         self.tokens_limit_per_sale_transaction()
             .set(&amount_of_tokens);
     }
 
-    #[only_owner]
     #[endpoint(setTokensLimitPerPresaleTransaction)]
     fn set_tokens_limit_per_presale_transaction(&self, amount_of_tokens: u32) {
+        self.require_role(Role::Admin);
+
         self.tokens_limit_per_presale_transaction()
             .set(&amount_of_tokens);
     }
 
-    #[only_owner]
+    // limits count over all transactions of a wallet, 0 means no limit
+    #[endpoint(setWalletMintLimits)]
+    fn set_wallet_mint_limits(&self, is_presale: bool, per_drop: u32, total: u32) {
+        self.require_role(Role::Admin);
+
+        self.wallet_mint_limit_per_drop(is_presale).set(&per_drop);
+        self.wallet_mint_limit(is_presale).set(&total);
+    }
+
     #[endpoint(setPrice)]
     fn set_price(&self, price: BigUint) {
+        self.require_role(Role::Admin);
+
         self.selling_price().set(&price);
     }
 
-    #[only_owner]
     #[endpoint(setPresalePrice)]
     fn set_presale_price(&self, price: BigUint) {
+        self.require_role(Role::Admin);
+
         self.presale_price().set(&price);
     }
 
-    #[only_owner]
+    // tiers are (from minted index, price, increment per token), the first
+    // one starts at 0, no tiers means the flat setPrice price
+    #[endpoint(setPriceSchedule)]
+    fn set_price_schedule(&self, tiers: MultiValueEncoded<MultiValue3<u32, BigUint, BigUint>>) {
+        self.require_role(Role::Admin);
+
+        self.price_schedule().clear();
+
+        let mut last_from_index = 0;
+
+        for tier in tiers.into_iter() {
+            let (from_index, price, increment) = tier.into_tuple();
+
+            if self.price_schedule().is_empty() {
+                require!(from_index == 0, "The first price tier should start at 0!");
+            } else {
+                require!(
+                    from_index > last_from_index,
+                    "Price tiers should be in increasing order!"
+                );
+            }
+            last_from_index = from_index;
+
+            self.price_schedule().push(&PriceTier {
+                from_index,
+                price,
+                increment,
+            });
+        }
+    }
+
+    #[endpoint(setPaymentPrice)]
+    fn set_payment_price(&self, token: TokenIdentifier, price: BigUint) {
+        self.require_role(Role::Admin);
+
+        require!(token.is_valid_esdt_identifier(), "Invalid token!");
+        require!(
+            self.dutch_auction().is_empty(),
+            "The dutch auction only accepts EGLD!"
+        );
+
+        self.payment_prices().insert(token, price);
+    }
+
+    #[endpoint(removePaymentPrice)]
+    fn remove_payment_price(&self, token: TokenIdentifier) {
+        self.require_role(Role::Admin);
+
+        self.payment_prices().remove(&token);
+    }
+
+    #[endpoint(setPresalePaymentPrice)]
+    fn set_presale_payment_price(&self, token: TokenIdentifier, price: BigUint) {
+        self.require_role(Role::Admin);
+
+        require!(token.is_valid_esdt_identifier(), "Invalid token!");
+
+        self.presale_payment_prices().insert(token, price);
+    }
+
+    #[endpoint(removePresalePaymentPrice)]
+    fn remove_presale_payment_price(&self, token: TokenIdentifier) {
+        self.require_role(Role::Admin);
+
+        self.presale_payment_prices().remove(&token);
+    }
+
     #[endpoint(setCid)]
     fn set_cid(&self, cid: ManagedBuffer) {
-        self.full_base_cid().set(&cid);
+        self.require_role(Role::Admin);
+
+        self.queue_action(&TimelockAction::SetCid(cid));
     }
 
-    #[only_owner]
     #[endpoint(setTokenName)]
     fn set_token_name(&self, token_name: ManagedBuffer) {
+        self.require_role(Role::Admin);
+
         self.nft_token_name().set(&token_name);
     }
 
-    #[only_owner]
     #[endpoint(setTags)]
     fn set_tags(&self, tags: ManagedBuffer) {
+        self.require_role(Role::Admin);
+
         self.tags().set(&tags);
     }
 
     // -----------------------
-    // funds methods
+    // timelock methods
     // -----------------------
 
-    // As an owner, claim Smart Contract balance.
-    #[only_owner]
-    #[endpoint(claimScFunds)]
-    fn claim_sc_funds(&self) {
-        self.send().direct_egld(
-            &self.blockchain().get_caller(),
-            &self
-                .blockchain()
-                .get_sc_balance(&TokenIdentifier::egld(), 0),
-            &[],
-        );
+    // the queue lives in the timelock module, the actions are applied here
+    #[endpoint(executeAction)]
+    fn execute_action(&self, action_id: u64) {
+        self.require_role(Role::Admin);
+
+        if let Some(action) = self.take_ready_action(action_id) {
+            self.apply_action(action);
+        }
     }
 
-    #[only_owner]
+    fn apply_action(&self, action: TimelockAction<Self::Api>) {
+        match action {
+            TimelockAction::SetCid(cid) => self.full_base_cid().set(&cid),
+            TimelockAction::SetRoyalties(royalties) => self.royalties().set(&royalties),
+            TimelockAction::SetTotalySupply(amount_of_tokens) => {
+                // the unminted indexes array is sized by the total supply
+                require!(
+                    !self.random_mint().get() || self.minted_indexes_total().get() == 0,
+                    "Total supply can't be changed after random minting started!"
+                );
+
+                self.amount_of_tokens_total().set(&amount_of_tokens)
+            }
+        }
+    }
+
+    // -----------------------
+    // sale phase methods
+    // -----------------------
+
+    #[endpoint(addSalePhase)]
+    fn add_sale_phase(
+        &self,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        price: BigUint,
+        whitelist_required: bool,
+        wallet_limit: u32,
+    ) -> u64 {
+        self.require_role(Role::Admin);
+
+        require!(
+            start_timestamp < end_timestamp,
+            "The phase should end after it starts!"
+        );
+
+        for (_, phase) in self.sale_phases().iter() {
+            require!(
+                end_timestamp <= phase.start_timestamp || start_timestamp >= phase.end_timestamp,
+                "Sale phases can't overlap!"
+            );
+        }
+
+        let phase_id = self.last_sale_phase_id().get() + 1;
+        self.last_sale_phase_id().set(&phase_id);
+
+        self.sale_phases().insert(
+            phase_id,
+            SalePhase {
+                start_timestamp,
+                end_timestamp,
+                price,
+                whitelist_required,
+                wallet_limit,
+            },
+        );
+
+        phase_id
+    }
+
+    #[endpoint(removeSalePhase)]
+    fn remove_sale_phase(&self, phase_id: u64) {
+        self.require_role(Role::Admin);
+
+        require!(
+            self.sale_phases().remove(&phase_id).is_some(),
+            "Sale phase not found!"
+        );
+    }
+
+    fn get_active_phase(&self) -> Option<(u64, SalePhase<Self::Api>)> {
+        let now = self.blockchain().get_block_timestamp();
+
+        self.sale_phases()
+            .iter()
+            .find(|(_, phase)| phase.start_timestamp <= now && now < phase.end_timestamp)
+    }
+
+    fn require_phase_eligibility(&self, phase_id: u64, phase: &SalePhase<Self::Api>, tokens: u32) {
+        if phase.whitelist_required {
+            require!(
+                self.get_is_whitelisted_per_current_drop(self.blockchain().get_caller()),
+                "Caller is not whitelisted!"
+            );
+        }
+
+        self.track_phase_mints(phase_id, phase, tokens);
+    }
+
+    fn track_phase_mints(&self, phase_id: u64, phase: &SalePhase<Self::Api>, tokens: u32) {
+        let caller = self.blockchain().get_caller();
+
+        if phase.wallet_limit > 0 {
+            let minted = self.minted_per_phase(phase_id, &caller).get();
+
+            require!(
+                minted + tokens <= phase.wallet_limit,
+                "The wallet limit for this phase is reached!"
+            );
+        }
+
+        self.minted_per_phase(phase_id, &caller)
+            .update(|minted| *minted += tokens);
+    }
+
+    // with a phase schedule the presale runs during the whitelist phases,
+    // which set its EGLD price and wallet limit; eligibility still comes from
+    // the presale settings
+    fn require_presale_open(&self) -> Option<(u64, SalePhase<Self::Api>)> {
+        if self.sale_phases().is_empty() {
+            require!(
+                !self.presale_is_open().is_empty(),
+                "The presale is not open!"
+            );
+
+            return None;
+        }
+
+        let active_phase = self.get_active_phase();
+        require!(
+            active_phase
+                .as_ref()
+                .map_or(false, |(_, phase)| phase.whitelist_required),
+            "No presale phase is active!"
+        );
+
+        active_phase
+    }
+
+    // -----------------------
+    // dutch auction methods
+    // -----------------------
+
+    #[endpoint(setDutchAuction)]
+    fn set_dutch_auction(
+        &self,
+        start_price: BigUint,
+        floor_price: BigUint,
+        price_step: BigUint,
+        step_interval: u64,
+        start_timestamp: u64,
+        rebate: OptionalValue<bool>,
+    ) {
+        self.require_role(Role::Admin);
+
+        require!(
+            start_price >= floor_price,
+            "The start price can't be lower than the floor price!"
+        );
+        require!(price_step > 0, "The price step should be greater than 0!");
+        require!(
+            step_interval > 0,
+            "The step interval should be greater than 0!"
+        );
+        require!(
+            self.payment_prices().is_empty(),
+            "The dutch auction only accepts EGLD, remove the ESDT prices first!"
+        );
+        self.require_auction_settled();
+
+        let id = self.last_auction_id().get() + 1;
+        self.last_auction_id().set(&id);
+
+        self.dutch_auction().set(&DutchAuction {
+            id,
+            start_price,
+            floor_price,
+            price_step,
+            step_interval,
+            start_timestamp,
+            rebate: rebate.into_option().unwrap_or_default(),
+        });
+    }
+
+    #[endpoint(unsetDutchAuction)]
+    fn unset_dutch_auction(&self) {
+        self.require_role(Role::Admin);
+
+        self.require_auction_settled();
+
+        self.dutch_auction().clear();
+    }
+
+    // fixes the clearing price and releases it to the payees, the
+    // difference stays in the contract for the rebates
+    #[endpoint(endDutchAuction)]
+    fn end_dutch_auction(&self) {
+        self.require_role(Role::Admin);
+
+        require!(!self.dutch_auction().is_empty(), "Auction is not set!");
+
+        let auction = self.dutch_auction().get();
+        require!(auction.rebate, "Auction has no rebate!");
+        require!(
+            !self.auction_ended(auction.id).get(),
+            "The auction has ended!"
+        );
+
+        self.auction_ended(auction.id).set(&true);
+
+        let minted = self.auction_total_minted(auction.id).get();
+        if minted == 0 {
+            return;
+        }
+
+        let clearing_price = self.auction_clearing_price(auction.id).get();
+        let released = (clearing_price - &auction.floor_price) * BigUint::from(minted);
+
+        self.auction_held_funds().update(|held| *held -= &released);
+        self.release_funds(None, &released);
+        self.distribute_mint_payment(&TokenIdentifier::egld(), &released);
+    }
+
+    #[endpoint(claimAuctionRebate)]
+    fn claim_auction_rebate(&self, auction_id: u64) {
+        require!(
+            self.auction_ended(auction_id).get(),
+            "The auction has not ended yet!"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let paid = self.auction_paid(auction_id, &caller).get();
+        let minted = self.auction_minted(auction_id, &caller).get();
+
+        let rebate = paid - self.auction_clearing_price(auction_id).get() * BigUint::from(minted);
+        require!(rebate > 0, "Nothing to claim!");
+
+        self.auction_paid(auction_id, &caller).clear();
+        self.auction_minted(auction_id, &caller).clear();
+        self.auction_held_funds().update(|held| *held -= &rebate);
+        self.release_funds(None, &rebate);
+
+        self.send().direct_egld(&caller, &rebate, &[]);
+    }
+
+    fn settle_auction_mint(&self, tokens: u32, payment_amount: &BigUint) {
+        let auction = self.dutch_auction().get();
+
+        // prices only go down, so the last sale sets the clearing price
+        self.auction_clearing_price(auction.id)
+            .set(&self.get_auction_price(&auction));
+
+        if !auction.rebate {
+            self.distribute_mint_payment(&TokenIdentifier::egld(), payment_amount);
+            return;
+        }
+
+        // everything above the floor is held until the auction ends
+        let floor_amount = &auction.floor_price * &BigUint::from(tokens);
+        let caller = self.blockchain().get_caller();
+
+        self.auction_paid(auction.id, &caller)
+            .update(|paid| *paid += payment_amount);
+        self.auction_minted(auction.id, &caller)
+            .update(|minted| *minted += tokens);
+        self.auction_total_minted(auction.id)
+            .update(|minted| *minted += tokens);
+        let held_amount = payment_amount - &floor_amount;
+        self.auction_held_funds()
+            .update(|held| *held += &held_amount);
+        self.reserve_funds(None, &held_amount);
+
+        self.distribute_mint_payment(&TokenIdentifier::egld(), &floor_amount);
+    }
+
+    fn get_auction_price(&self, auction: &DutchAuction<Self::Api>) -> BigUint {
+        let now = self.blockchain().get_block_timestamp();
+        if now <= auction.start_timestamp {
+            return auction.start_price.clone();
+        }
+
+        let steps = (now - auction.start_timestamp) / auction.step_interval;
+        let price_drop = &auction.price_step * &BigUint::from(steps);
+
+        if price_drop >= &auction.start_price - &auction.floor_price {
+            auction.floor_price.clone()
+        } else {
+            &auction.start_price - &price_drop
+        }
+    }
+
+    fn require_auction_settled(&self) {
+        if self.dutch_auction().is_empty() {
+            return;
+        }
+
+        let auction = self.dutch_auction().get();
+
+        require!(
+            !auction.rebate
+                || self.auction_total_minted(auction.id).get() == 0
+                || self.auction_ended(auction.id).get(),
+            "The auction rebates are not settled!"
+        );
+    }
+
+    // -----------------------
+    // revenue split methods
+    // -----------------------
+
+    #[endpoint(setPayees)]
+    fn set_payees(&self, payees: MultiValueEncoded<MultiValue2<ManagedAddress, u32>>) {
+        self.require_role(Role::Admin);
+
+        self.payees().clear();
+
+        let mut shares_sum = 0u32;
+
+        for payee in payees.into_iter() {
+            let (address, share) = payee.into_tuple();
+
+            require!(!address.is_zero(), "Invalid address!");
+            require!(share > 0, "Payee share should be greater than 0!");
+            require!(
+                self.payees().insert(address, share).is_none(),
+                "Duplicate payee!"
+            );
+
+            shares_sum += share;
+        }
+
+        require!(
+            self.payees().is_empty() || shares_sum == SHARES_TOTAL,
+            "Payee shares should add up to 10000!"
+        );
+    }
+
+    #[endpoint(setAccumulatePayouts)]
+    fn set_accumulate_payouts(&self, accumulate: bool) {
+        self.require_role(Role::Admin);
+
+        self.accumulate_payouts().set(&accumulate);
+    }
+
+    // payouts are kept per payment token, EGLD when no token is given
+    #[endpoint(withdrawPayout)]
+    fn withdraw_payout(&self, token: OptionalValue<TokenIdentifier>) {
+        let token = token.into_option().unwrap_or_else(TokenIdentifier::egld);
+        let caller = self.blockchain().get_caller();
+        let balance = self.payout_balance(&caller, &token).get();
+
+        require!(balance > 0, "Nothing to withdraw!");
+
+        self.payout_balance(&caller, &token).clear();
+        self.total_pending_payouts(&token)
+            .update(|total| *total -= &balance);
+        self.release_funds(self.reserve_token(&token), &balance);
+
+        self.send().direct(&caller, &token, 0, &balance, &[]);
+    }
+
+    // share is in basis points of every mint payment
+    #[endpoint(setStakingRewardShare)]
+    fn set_staking_reward_share(&self, staking_contract: ManagedAddress, share: u32) {
+        self.require_role(Role::Admin);
+
+        require!(
+            self.blockchain().is_smart_contract(&staking_contract),
+            "Invalid staking contract!"
+        );
+        require!(share <= SHARES_TOTAL, "Staking share can't exceed 10000!");
+
+        self.staking_contract().set(&staking_contract);
+        self.staking_reward_share().set(&share);
+    }
+
+    #[endpoint(unsetStakingRewardShare)]
+    fn unset_staking_reward_share(&self) {
+        self.require_role(Role::Admin);
+
+        self.staking_contract().clear();
+        self.staking_reward_share().clear();
+    }
+
+    // discount is in basis points of the presale price, free mints win
+    // over the discount when both are set
+    #[endpoint(setStakerPerks)]
+    fn set_staker_perks(&self, free_mints_per_nft: u32, discount: u32) {
+        self.require_role(Role::Admin);
+
+        require!(discount <= SHARES_TOTAL, "Discount can't exceed 10000!");
+
+        self.staker_free_mints_per_nft().set(&free_mints_per_nft);
+        self.staker_discount().set(&discount);
+    }
+
+    // sends the staking share to the staking contract reward pool and
+    // returns the forwarded amount
+    fn forward_staking_share(&self, payment_amount: &BigUint) -> BigUint {
+        if self.staking_contract().is_empty() {
+            return BigUint::zero();
+        }
+
+        let staking_amount = payment_amount * &BigUint::from(self.staking_reward_share().get())
+            / BigUint::from(SHARES_TOTAL);
+
+        if staking_amount > 0 {
+            self.staking_contract_proxy(self.staking_contract().get())
+                .deposit_reward()
+                .with_egld_transfer(staking_amount.clone())
+                .with_gas_limit(STAKING_DEPOSIT_GAS_LIMIT)
+                .transfer_execute();
+        }
+
+        staking_amount
+    }
+
+    #[proxy]
+    fn staking_contract_proxy(&self, sc_address: ManagedAddress)
+        -> staking_proxy::Proxy<Self::Api>;
+
+    // -----------------------
+    // funds methods
+    // -----------------------
+
+    // As an owner, claim Smart Contract balance.
+    #[endpoint(claimScFunds)]
+    fn claim_sc_funds(&self) {
+        self.require_role(Role::Treasurer);
+
+        require!(
+            self.signers().is_empty(),
+            "Withdrawals require multisig approval!"
+        );
+
+        // payouts waiting to be withdrawn and held auction funds stay in the
+        // contract, same as for multisig withdrawals
+        self.send().direct_egld(
+            &self.blockchain().get_caller(),
+            &self.get_free_balance(&None),
+            &[],
+        );
+    }
+
+    // the marketplace sends the royalties to the given receiver, the
+    // configured treasury, or to this contract to split them between payees
     #[endpoint(claimTokens)]
-    fn claim_tokens(&self, token: TokenIdentifier, nonce: u32) {
-        let receiver = hex!("c2e210583b5f6dca60cb7d02dda119c91af2e83803a4349c4847419459cba975");
+    fn claim_tokens(
+        &self,
+        marketplace: ManagedAddress,
+        token: TokenIdentifier,
+        nonce: u64,
+        receiver: OptionalValue<ManagedAddress>,
+    ) {
+        self.require_role(Role::Treasurer);
+
+        let claim_function = self.marketplaces().get(&marketplace);
+        require!(claim_function.is_some(), "Marketplace not registered!");
+
+        let receiver = match receiver.into_option() {
+            Some(receiver) => receiver,
+            None if self.forward_royalties_to_splits().get() => self.blockchain().get_sc_address(),
+            None => {
+                require!(
+                    !self.royalties_treasury().is_empty(),
+                    "No receiver set for the claimed royalties!"
+                );
+
+                self.royalties_treasury().get()
+            }
+        };
 
         let mut arg_buffer = ManagedArgBuffer::new_empty();
-        arg_buffer.push_arg(token);
+        arg_buffer.push_arg(&token);
         arg_buffer.push_arg(nonce);
-        arg_buffer.push_arg(receiver);
-
-        let mut result = ManagedBuffer::new();
-        let function = "claimTokens";
-        result.append_bytes(&function.as_bytes());
+        arg_buffer.push_arg(&receiver);
 
         self.send()
-            .contract_call::<()>(ManagedAddress::new_from_bytes(&MARKETPLACE), result)
-            .with_egld_transfer(BigUint::zero())
+            .contract_call::<()>(marketplace.clone(), claim_function.unwrap())
             .with_arguments_raw(arg_buffer)
             .async_call()
+            .with_callback(
+                self.callbacks()
+                    .claim_tokens_callback(marketplace, token, receiver),
+            )
             .call_and_exit();
     }
 
-    #[only_owner]
+    #[callback]
+    fn claim_tokens_callback(
+        &self,
+        marketplace: ManagedAddress,
+        token: TokenIdentifier,
+        receiver: ManagedAddress,
+        #[call_result] result: ManagedAsyncCallResult<IgnoreValue>,
+    ) {
+        match result {
+            ManagedAsyncCallResult::Ok(_) => {
+                let claimed_amount = self.get_callback_amount(&token);
+
+                self.royalties_claimed_event(&marketplace, &token, &receiver);
+
+                if receiver == self.blockchain().get_sc_address() && claimed_amount > 0 {
+                    self.split_to_payees(&token, &claimed_amount);
+                }
+            }
+            ManagedAsyncCallResult::Err(err) => {
+                self.royalties_claim_failed_event(&marketplace, &token, &err.err_msg);
+            }
+        }
+    }
+
+    // royalties sent to this contract during the claim come with the callback
+    fn get_callback_amount(&self, token: &TokenIdentifier) -> BigUint {
+        if token.is_egld() {
+            return self.call_value().egld_value();
+        }
+
+        let mut amount = BigUint::zero();
+        for payment in self.call_value().all_esdt_transfers().iter() {
+            if &payment.token_identifier == token && payment.token_nonce == 0 {
+                amount += &payment.amount;
+            }
+        }
+
+        amount
+    }
+
+    #[endpoint(addMarketplace)]
+    fn add_marketplace(&self, marketplace: ManagedAddress, claim_function: ManagedBuffer) {
+        self.require_role(Role::Admin);
+
+        require!(
+            self.blockchain().is_smart_contract(&marketplace),
+            "Invalid marketplace!"
+        );
+        require!(!claim_function.is_empty(), "Invalid claim function!");
+
+        self.marketplaces().insert(marketplace, claim_function);
+    }
+
+    #[endpoint(removeMarketplace)]
+    fn remove_marketplace(&self, marketplace: ManagedAddress) {
+        self.require_role(Role::Admin);
+
+        require!(
+            self.marketplaces().remove(&marketplace).is_some(),
+            "Marketplace not registered!"
+        );
+    }
+
+    #[endpoint(setRoyaltiesTreasury)]
+    fn set_royalties_treasury(&self, treasury: OptionalValue<ManagedAddress>) {
+        self.require_role(Role::Admin);
+
+        match treasury.into_option() {
+            Some(treasury) => {
+                require!(!treasury.is_zero(), "Invalid address!");
+
+                self.royalties_treasury().set(&treasury);
+            }
+            None => self.royalties_treasury().clear(),
+        }
+    }
+
+    #[endpoint(setForwardRoyaltiesToSplits)]
+    fn set_forward_royalties_to_splits(&self, forward: bool) {
+        self.require_role(Role::Admin);
+
+        self.forward_royalties_to_splits().set(&forward);
+    }
+
     #[payable("*")]
     #[endpoint(updateTokenAttributes)]
     fn update_token_attributes(&self, nonce: u32) {
+        self.require_role(Role::Admin);
+
         // use alloc::string::ToString;
 
         let token_id = self.nft_token_id().get();
@@ -525,93 +1485,369 @@ pub trait ElvenTools {
     // minting methods
     // -----------------------
 
-    #[payable("EGLD")]
+    #[payable("*")]
     #[endpoint(mintPresale)]
     fn mint_presale(
         &self,
+        #[payment_token] payment_token: TokenIdentifier,
+        #[payment_nonce] payment_nonce: u64,
         #[payment_amount] payment_amount: BigUint,
         token_amount: OptionalValue<u32>,
+        merkle_proof: OptionalValue<MerkleProof<Self::Api>>,
     ) {
-        // require: presale should be open
-        require!(
-            !self.presale_is_open().is_empty(),
-            "The presale is not open!"
-        );
+        // get caller address
+        let caller = self.blockchain().get_caller();
 
-        // require: drop should be set
-        require!(
-            !self.amount_of_tokens_per_drop().is_empty(),
-            "Drop is not set!"
-        );
+        // get amount of tokens to mint
+        let mut tokens = token_amount.into_option().unwrap_or_default();
 
-        // require: tokens limit should be set
-        require!(
-            !self.tokens_limit_per_presale_transaction().is_empty(),
-            "Tokens limit per transaction is not set!"
-        );
+        // amount of tokens to mint should be greater than 0
+        if tokens < 1 {
+            tokens = 1
+        }
 
-        // require: Token should be issued
-        require!(!self.nft_token_id().is_empty(), "Token not issued!");
+        self.require_presale_mint_allowed(tokens);
+
+        let merkle_proof = merkle_proof.into_option();
+        let whitelist_tier = match &merkle_proof {
+            Some(_) => None,
+            None => self.get_best_whitelist_tier(&caller, tokens),
+        };
+
+        // require: caller should be whitelisted, by proof, by tier or by address
+        match (&merkle_proof, &whitelist_tier) {
+            (Some(proof), _) => self.require_valid_merkle_proof(&caller, proof),
+            (None, Some(_)) => {}
+            (None, None) => require!(
+                self.get_is_whitelisted_per_current_drop(caller.clone()),
+                "Caller is not whitelisted!"
+            ),
+        }
 
-        // get vars to read ESDTNFTCreate role
-        let token = self.nft_token_id().get();
-        let roles = self.blockchain().get_esdt_local_roles(&token);
+        let price_tag = match (&merkle_proof, &whitelist_tier) {
+            (Some(proof), _) if proof.price_tier > 0 => {
+                // tier prices are set in EGLD only
+                require!(payment_token.is_egld(), "Payment token not accepted!");
 
-        // require: ESDTNFTCreate Role
+                self.get_merkle_tier_price(proof.price_tier)
+            }
+            (None, Some((_, tier))) => {
+                require!(payment_token.is_egld(), "Payment token not accepted!");
+
+                tier.price.clone()
+            }
+            _ => self.get_presale_price_for(&payment_token, payment_nonce),
+        };
+
+        if let Some(proof) = &merkle_proof {
+            self.track_merkle_mints(&caller, proof, tokens);
+        }
+
+        if let Some((tier_id, _)) = &whitelist_tier {
+            self.minted_per_whitelist_tier(self.opened_drop().get(), *tier_id, &caller)
+                .update(|minted| *minted += tokens);
+        }
+
+        // require: payment amount should be equal to the cost of the tokens
         require!(
-            roles.has_role(&EsdtLocalRole::NftCreate),
-            "ESDTNFTCreate role not set!"
+            payment_amount == price_tag * BigUint::from(tokens),
+            "Invalid amount as payment"
         );
 
-        // get caller address
+        self.track_wallet_mints(true, tokens);
+
+        // start minting
+        for _ in 0..tokens {
+            self.mint_single_nft(OptionalValue::None)
+        }
+
+        self.distribute_mint_payment(&payment_token, &payment_amount);
+    }
+
+    // holders prove ownership through their balance, which only works for
+    // accounts in the same shard as the minter
+    #[payable("*")]
+    #[endpoint(mintPresaleAsHolder)]
+    fn mint_presale_as_holder(
+        &self,
+        #[payment_token] payment_token: TokenIdentifier,
+        #[payment_nonce] payment_nonce: u64,
+        #[payment_amount] payment_amount: BigUint,
+        collection: TokenIdentifier,
+        nonces: MultiValueEncoded<u64>,
+    ) {
+        let caller = self.blockchain().get_caller();
+        let mut holdings = ManagedVec::new();
+
+        for nonce in nonces.into_iter() {
+            require!(
+                self.blockchain()
+                    .get_esdt_balance(&caller, &collection, nonce)
+                    > 0,
+                "Caller does not hold the token!"
+            );
+
+            holdings.push(EsdtTokenPayment::new(
+                collection.clone(),
+                nonce,
+                BigUint::from(NFT_AMOUNT),
+            ));
+        }
+
+        self.gated_presale_mint(&holdings, &payment_token, payment_nonce, &payment_amount);
+    }
+
+    // EGLD can't join a multi transfer, so holders paying in EGLD deposit
+    // the exact cost before calling mintPresaleWithNfts
+    #[payable("EGLD")]
+    #[endpoint(depositPresalePayment)]
+    fn deposit_presale_payment(&self, #[payment_amount] payment_amount: BigUint) {
+        require!(payment_amount > 0, "Nothing to deposit!");
+
+        let caller = self.blockchain().get_caller();
+        self.presale_deposit(&caller)
+            .update(|deposit| *deposit += &payment_amount);
+
+        self.reserve_funds(None, &payment_amount);
+    }
+
+    #[endpoint(withdrawPresalePayment)]
+    fn withdraw_presale_payment(&self) {
         let caller = self.blockchain().get_caller();
+        let deposit = self.presale_deposit(&caller).get();
+
+        require!(deposit > 0, "Nothing to withdraw!");
+
+        self.presale_deposit(&caller).clear();
+        self.release_funds(None, &deposit);
+        self.send().direct_egld(&caller, &deposit, &[]);
+    }
+
+    // holders send their NFTs along and get them back in the same call, the
+    // price is paid with an ESDT sent along or with the EGLD deposit
+    #[payable("*")]
+    #[endpoint(mintPresaleWithNfts)]
+    fn mint_presale_with_nfts(&self) {
+        let caller = self.blockchain().get_caller();
+        let mut holdings = ManagedVec::new();
+        let mut payment: Option<EsdtTokenPayment<Self::Api>> = None;
+
+        for transfer in self.call_value().all_esdt_transfers().iter() {
+            if transfer.token_nonce > 0
+                && self
+                    .gated_collections()
+                    .contains_key(&transfer.token_identifier)
+            {
+                holdings.push(transfer);
+            } else {
+                require!(
+                    payment.is_none(),
+                    "Only one payment is accepted besides the NFTs!"
+                );
+
+                payment = Some(transfer);
+            }
+        }
+
+        let (payment_token, payment_nonce, payment_amount) = match payment {
+            Some(payment) => (
+                payment.token_identifier,
+                payment.token_nonce,
+                payment.amount,
+            ),
+            None => {
+                let deposit = self.presale_deposit(&caller).get();
 
-        // require: caller should be whitelisted
+                self.presale_deposit(&caller).clear();
+                self.release_funds(None, &deposit);
+
+                (TokenIdentifier::egld(), 0, deposit)
+            }
+        };
+
+        self.gated_presale_mint(&holdings, &payment_token, payment_nonce, &payment_amount);
+
+        // give the NFTs used as proof back
+        self.send().direct_multi(&caller, &holdings, &[]);
+    }
+
+    // every token nonce grants the allowance of its collection once per drop
+    fn gated_presale_mint(
+        &self,
+        holdings: &ManagedVec<EsdtTokenPayment<Self::Api>>,
+        payment_token: &TokenIdentifier,
+        payment_nonce: u64,
+        payment_amount: &BigUint,
+    ) {
+        require!(!holdings.is_empty(), "No gated tokens provided!");
+
+        let drop_id = self.opened_drop().get();
+        let mut tokens = 0u32;
+
+        for holding in holdings.iter() {
+            let allowance = self.gated_collections().get(&holding.token_identifier);
+            require!(allowance.is_some(), "Collection is not gated!");
+
+            let nonce_used =
+                self.gated_nonce_used(drop_id, &holding.token_identifier, holding.token_nonce);
+            require!(nonce_used.is_empty(), "Token already used in this drop!");
+
+            nonce_used.set(&true);
+            tokens += allowance.unwrap();
+        }
+
+        self.require_presale_mint_allowed(tokens);
+
+        let price_tag = self.get_presale_price_for(payment_token, payment_nonce);
+
+        // require: payment amount should be equal to the cost of the tokens
         require!(
-            self.get_is_whitelisted_per_current_drop(caller),
-            "Caller is not whitelisted!"
+            payment_amount == &(price_tag * BigUint::from(tokens)),
+            "Invalid amount as payment"
         );
 
-        // get amount of tokens to mint
-        let mut tokens = token_amount.into_option().unwrap_or_default();
+        self.track_wallet_mints(true, tokens);
 
-        // amount of tokens to mint should be greater than 0
-        if tokens < 1 {
-            tokens = 1
+        // start minting
+        for _ in 0..tokens {
+            self.mint_single_nft(OptionalValue::None)
         }
 
-        let max_tokens = self.tokens_limit_per_presale_transaction().get();
+        self.distribute_mint_payment(payment_token, payment_amount);
+    }
 
-        // require: Tokens should be lower than max_tokens
+    // every staked NFT grants its free mints, or one discounted mint, once
+    // per drop; receipt holders pass their receipt nonces to use the NFTs
+    // behind them. The staking contract has to live in the same shard since
+    // it is queried synchronously
+    #[payable("*")]
+    #[endpoint(mintAsStaker)]
+    fn mint_as_staker(
+        &self,
+        #[payment_token] payment_token: TokenIdentifier,
+        #[payment_nonce] payment_nonce: u64,
+        #[payment_amount] payment_amount: BigUint,
+        token_amount: u32,
+        receipt_nonces: MultiValueEncoded<u64>,
+    ) {
         require!(
-            tokens <= max_tokens,
-            "The number of tokens to mint should be lower than the max tokens per transaction!"
+            !self.staking_contract().is_empty(),
+            "Staking contract is not set!"
         );
 
-        // require: There should be enough tokens left to mint in this drop
+        let free_mints_per_nft = self.staker_free_mints_per_nft().get();
+        let discount = self.staker_discount().get();
         require!(
-            self.get_current_left_tokens_amount() >= tokens,
-            "All tokens have been minted already for this presale!"
+            free_mints_per_nft > 0 || discount > 0,
+            "Staker perks are not set!"
         );
 
-        let single_payment_amount = payment_amount / tokens;
-        let price_tag = self.presale_price().get();
+        // amount of tokens to mint should be greater than 0
+        let tokens = if token_amount < 1 { 1 } else { token_amount };
 
-        // require: payment amount should be equal to the price tag
-        require!(
-            single_payment_amount == price_tag,
-            "Invalid amount as payment"
-        );
+        self.require_presale_mint_allowed(tokens);
+
+        let staked_nfts = self.get_caller_staked_nfts(receipt_nonces);
+
+        // a free mint perk lets one staked NFT cover several mints, which
+        // can be used over several calls
+        let mints_per_nft = if free_mints_per_nft > 0 {
+            free_mints_per_nft
+        } else {
+            1
+        };
+        let mut mints_left = tokens;
+
+        let drop_id = self.opened_drop().get();
+
+        for nonce in staked_nfts.iter() {
+            if mints_left == 0 {
+                break;
+            }
+
+            let used = self.staked_nft_mints(drop_id, nonce).get();
+            if used >= mints_per_nft {
+                continue;
+            }
+
+            let mints = core::cmp::min(mints_per_nft - used, mints_left);
+            self.staked_nft_mints(drop_id, nonce).set(&(used + mints));
+            mints_left -= mints;
+        }
+
+        require!(mints_left == 0, "Not enough unused staked NFTs!");
+
+        let mint_cost = if free_mints_per_nft > 0 {
+            BigUint::zero()
+        } else {
+            self.get_presale_price_for(&payment_token, payment_nonce)
+                * BigUint::from(tokens)
+                * BigUint::from(SHARES_TOTAL - discount)
+                / BigUint::from(SHARES_TOTAL)
+        };
+
+        // require: payment amount should be equal to the cost of the tokens
+        require!(payment_amount == mint_cost, "Invalid amount as payment");
+
+        self.track_wallet_mints(true, tokens);
+
+        // start minting
+        for _ in 0..tokens {
+            self.mint_single_nft(OptionalValue::None)
+        }
+
+        self.distribute_mint_payment(&payment_token, &payment_amount);
+    }
+
+    // NFTs behind a receipt are staked under the receipt position, so they
+    // count for whoever holds the receipt
+    fn get_caller_staked_nfts(&self, receipt_nonces: MultiValueEncoded<u64>) -> ManagedVec<u64> {
+        let caller = self.blockchain().get_caller();
+        let staking_contract = self.staking_contract().get();
+
+        let mut staked_nfts = ManagedVec::new();
+        let nfts: MultiValueEncoded<u64> = self
+            .staking_contract_proxy(staking_contract.clone())
+            .get_staked_nfts(caller.clone())
+            .execute_on_dest_context();
+
+        for nonce in nfts.into_iter() {
+            staked_nfts.push(nonce);
+        }
+
+        if receipt_nonces.is_empty() {
+            return staked_nfts;
+        }
 
-        // start minting
-        for _ in 0..tokens {
-            self.mint_single_nft(single_payment_amount.clone(), OptionalValue::None)
+        let receipt_token: TokenIdentifier = self
+            .staking_contract_proxy(staking_contract.clone())
+            .get_receipt_token_id()
+            .execute_on_dest_context();
+
+        for receipt_nonce in receipt_nonces.into_iter() {
+            require!(
+                self.blockchain()
+                    .get_esdt_balance(&caller, &receipt_token, receipt_nonce)
+                    > 0,
+                "Caller does not hold the receipt!"
+            );
+
+            let nonce: u64 = self
+                .staking_contract_proxy(staking_contract.clone())
+                .get_nft_by_receipt(receipt_nonce)
+                .execute_on_dest_context();
+            require!(nonce > 0, "Receipt is not active!");
+
+            staked_nfts.push(nonce);
         }
+
+        staked_nfts
     }
 
-    #[only_owner]
     #[endpoint(giveaway)]
     fn giveaway(&self, address: ManagedAddress, amount_of_tokens: u32) {
+        self.require_role(Role::Admin);
+
         require!(!self.nft_token_id().is_empty(), "Token not issued!");
 
         let token = self.nft_token_id().get();
@@ -628,13 +1864,14 @@ pub trait ElvenTools {
         );
 
         for _ in 0..amount_of_tokens {
-            self.mint_single_nft(BigUint::zero(), OptionalValue::Some(address.clone()))
+            self.mint_single_nft(OptionalValue::Some(address.clone()))
         }
     }
 
-    #[only_owner]
     #[endpoint(lolMint)]
     fn lol_mint(&self, address: ManagedAddress, nonce: u32) {
+        self.require_role(Role::Admin);
+
         require!(!self.nft_token_id().is_empty(), "Token not issued!");
 
         let token = self.nft_token_id().get();
@@ -651,15 +1888,28 @@ pub trait ElvenTools {
         );
 
         for _ in 0..nonce {
-            self.mint_single_nft(BigUint::zero(), OptionalValue::Some(address.clone()));
+            self.mint_single_nft(OptionalValue::Some(address.clone()));
         }
     }
 
-    #[payable("EGLD")]
+    #[payable("*")]
     #[endpoint(mint)]
-    fn mint(&self, #[payment_amount] payment_amount: BigUint, token_amount: OptionalValue<u32>) {
-        // require: Sale should be open
-        require!(self.paused().is_empty(), "The public sale is not open!");
+    fn mint(
+        &self,
+        #[payment_token] payment_token: TokenIdentifier,
+        #[payment_nonce] payment_nonce: u64,
+        #[payment_amount] payment_amount: BigUint,
+        token_amount: OptionalValue<u32>,
+    ) {
+        // with a phase schedule the block timestamp decides if the sale is open
+        let active_phase = self.get_active_phase();
+
+        if self.sale_phases().is_empty() {
+            // require: Sale should be open
+            require!(self.paused().is_empty(), "The public sale is not open!");
+        } else {
+            require!(active_phase.is_some(), "No sale phase is active!");
+        }
 
         // require: drop should be set
         require!(
@@ -708,43 +1958,55 @@ pub trait ElvenTools {
             "All tokens have been minted already (totally or per drop)!"
         );
 
-        // Get caller details
-        let single_payment_amount = payment_amount / tokens;
-        let price_tag = self.selling_price().get();
+        let mint_cost = match &active_phase {
+            Some((phase_id, phase)) => {
+                self.require_phase_eligibility(*phase_id, phase, tokens);
 
-        // require: payment amount should be equal to the price tag
-        require!(
-            single_payment_amount == price_tag,
-            "Invalid amount as payment"
-        );
+                if payment_token.is_egld() {
+                    &phase.price * &BigUint::from(tokens)
+                } else {
+                    self.get_price_for(&payment_token, payment_nonce) * BigUint::from(tokens)
+                }
+            }
+            None => self.get_mint_cost(&payment_token, payment_nonce, tokens),
+        };
+
+        // require: payment amount should be equal to the cost of the tokens
+        require!(payment_amount == mint_cost, "Invalid amount as payment");
+
+        self.track_wallet_mints(false, tokens);
 
         // start minting
         for _ in 0..tokens {
-            self.mint_single_nft(single_payment_amount.clone(), OptionalValue::None);
+            self.mint_single_nft(OptionalValue::None);
+        }
+
+        if active_phase.is_some() || self.dutch_auction().is_empty() {
+            self.distribute_mint_payment(&payment_token, &payment_amount);
+        } else {
+            self.settle_auction_mint(tokens, &payment_amount);
         }
     }
 
     // Private single token mint function. It is also used for the giveaway.
-    fn mint_single_nft(
-        &self,
-        payment_amount: BigUint,
-        giveaway_address: OptionalValue<ManagedAddress>,
-    ) {
+    fn mint_single_nft(&self, giveaway_address: OptionalValue<ManagedAddress>) {
         let amount = &BigUint::from(NFT_AMOUNT);
 
         let token = self.nft_token_id().get();
         // let token_name = self.nft_token_name().get();
-        let token_name = self.build_token_name();
+        let index_to_mint = self.take_index_to_mint(&giveaway_address);
+
+        let token_name = self.build_token_name(index_to_mint);
 
         let royalties = self.royalties().get();
 
-        let attributes = self.build_attributes_buffer();
+        let attributes = self.build_attributes_buffer(index_to_mint);
 
         let hash_buffer = self.crypto().sha256(&attributes);
 
         let attributes_hash = hash_buffer.as_managed_buffer();
 
-        let uris = self.build_uris_vec();
+        let uris = self.build_uris_vec(index_to_mint);
 
         let nonce = self.send().esdt_nft_create(
             &token,
@@ -780,15 +2042,78 @@ pub trait ElvenTools {
             &BigUint::from(NFT_AMOUNT),
             &[],
         );
+    }
+
+    // Forwards the staking share of EGLD payments, then splits the rest
+    // between the payees.
+    fn distribute_mint_payment(&self, payment_token: &TokenIdentifier, payment_amount: &BigUint) {
+        self.revenue_by_token(payment_token)
+            .update(|revenue| *revenue += payment_amount);
 
-        if payment_amount > 0 {
-            let payment_nonce: u64 = 0;
-            let payment_token = &TokenIdentifier::egld();
+        let payment_amount = if payment_token.is_egld() {
+            let staking_amount = self.forward_staking_share(payment_amount);
+
+            payment_amount - &staking_amount
+        } else {
+            payment_amount.clone()
+        };
+
+        self.split_to_payees(payment_token, &payment_amount);
+    }
+
+    // The owner gets everything when no payees are set.
+    fn split_to_payees(&self, payment_token: &TokenIdentifier, payment_amount: &BigUint) {
+        if payment_amount == &0 {
+            return;
+        }
+
+        if self.payees().is_empty() {
             let owner = self.blockchain().get_owner_address();
 
             // send payment to contract owner
             self.send()
-                .direct(&owner, &payment_token, payment_nonce, &payment_amount, &[]);
+                .direct(&owner, payment_token, 0, payment_amount, &[]);
+            return;
+        }
+
+        let accumulate = self.accumulate_payouts().get();
+        let mut left_amount = payment_amount.clone();
+        let mut payees_left = self.payees().len();
+
+        for (payee, share) in self.payees().iter() {
+            payees_left -= 1;
+
+            // rounding leftovers go to the last payee
+            let payee_amount = if payees_left == 0 {
+                left_amount.clone()
+            } else {
+                payment_amount * &BigUint::from(share) / BigUint::from(SHARES_TOTAL)
+            };
+            left_amount -= &payee_amount;
+
+            if payee_amount == 0 {
+                continue;
+            }
+
+            if accumulate {
+                self.payout_balance(&payee, payment_token)
+                    .update(|balance| *balance += &payee_amount);
+                self.total_pending_payouts(payment_token)
+                    .update(|total| *total += &payee_amount);
+                self.reserve_funds(self.reserve_token(payment_token), &payee_amount);
+            } else {
+                self.send()
+                    .direct(&payee, payment_token, 0, &payee_amount, &[]);
+            }
+        }
+    }
+
+    // the multisig reserve books EGLD without a token
+    fn reserve_token(&self, token: &TokenIdentifier) -> Option<TokenIdentifier> {
+        if token.is_egld() {
+            None
+        } else {
+            Some(token.clone())
         }
     }
 
@@ -796,6 +2121,176 @@ pub trait ElvenTools {
     // minting utils methods
     // -----------------------
 
+    fn track_wallet_mints(&self, is_presale: bool, tokens: u32) {
+        let caller = self.blockchain().get_caller();
+
+        require!(
+            tokens <= self.get_remaining_allowance(caller.clone(), is_presale),
+            "The wallet mint limit is reached!"
+        );
+
+        let drop_id = self.opened_drop().get();
+
+        self.minted_by_wallet_per_drop(is_presale, drop_id, &caller)
+            .update(|minted| *minted += tokens);
+        self.minted_by_wallet(is_presale, &caller)
+            .update(|minted| *minted += tokens);
+    }
+
+    // EGLD mints follow the price schedule when there is no dutch auction,
+    // a multi-token mint may span several tiers
+    fn get_mint_cost(
+        &self,
+        payment_token: &TokenIdentifier,
+        payment_nonce: u64,
+        tokens: u32,
+    ) -> BigUint {
+        if payment_token.is_egld()
+            && self.dutch_auction().is_empty()
+            && !self.price_schedule().is_empty()
+        {
+            return self.get_schedule_cost(self.minted_indexes_total().get(), tokens);
+        }
+
+        self.get_price_for(payment_token, payment_nonce) * BigUint::from(tokens)
+    }
+
+    fn get_schedule_cost(&self, minted_tokens: u32, tokens: u32) -> BigUint {
+        let schedule = self.price_schedule();
+        let first_index = minted_tokens as u64;
+        let end_index = first_index + tokens as u64;
+        let mut cost = BigUint::zero();
+
+        for position in 1..=schedule.len() {
+            let tier = schedule.get(position);
+            let tier_end = if position < schedule.len() {
+                schedule.get(position + 1).from_index as u64
+            } else {
+                u64::MAX
+            };
+
+            // part of the minted range priced by this tier
+            let from = core::cmp::max(first_index, tier.from_index as u64);
+            let to = core::cmp::min(end_index, tier_end);
+            if from >= to {
+                continue;
+            }
+
+            // sum of price + increment * (index - tier start) over the range
+            let count = to - from;
+            let offset = from - tier.from_index as u64;
+            let steps = count * offset + count * (count - 1) / 2;
+
+            cost += tier.price * BigUint::from(count) + tier.increment * BigUint::from(steps);
+        }
+
+        cost
+    }
+
+    // EGLD is priced by setPrice or the running dutch auction, ESDT tokens
+    // by the payment price list
+    fn get_price_for(&self, payment_token: &TokenIdentifier, payment_nonce: u64) -> BigUint {
+        if !self.dutch_auction().is_empty() {
+            require!(
+                payment_token.is_egld(),
+                "Only EGLD is accepted during the auction!"
+            );
+
+            let auction = self.dutch_auction().get();
+            require!(
+                self.blockchain().get_block_timestamp() >= auction.start_timestamp,
+                "The auction has not started yet!"
+            );
+            require!(
+                !self.auction_ended(auction.id).get(),
+                "The auction has ended!"
+            );
+
+            return self.get_auction_price(&auction);
+        }
+
+        if payment_token.is_egld() {
+            return self.selling_price().get();
+        }
+
+        let price = self.payment_prices().get(payment_token);
+        require!(
+            payment_nonce == 0 && price.is_some(),
+            "Payment token not accepted!"
+        );
+
+        price.unwrap()
+    }
+
+    fn get_presale_price_for(
+        &self,
+        payment_token: &TokenIdentifier,
+        payment_nonce: u64,
+    ) -> BigUint {
+        if payment_token.is_egld() {
+            return match self.get_active_phase() {
+                Some((_, phase)) => phase.price,
+                None => self.presale_price().get(),
+            };
+        }
+
+        let price = self.presale_payment_prices().get(payment_token);
+        require!(
+            payment_nonce == 0 && price.is_some(),
+            "Payment token not accepted!"
+        );
+
+        price.unwrap()
+    }
+
+    // In random mint mode the index is drawn from a swap-and-pop array of
+    // the unminted indexes, where an empty slot at position `i` holds `i + 1`.
+    fn take_index_to_mint(&self, giveaway_address: &OptionalValue<ManagedAddress>) -> u32 {
+        if !self.random_mint().get() {
+            return self.next_index_to_mint().get();
+        }
+
+        // contracts could revert the whole tx until they get a rare index
+        if let OptionalValue::None = giveaway_address {
+            let caller = self.blockchain().get_caller();
+            require!(
+                !self.blockchain().is_smart_contract(&caller),
+                "Smart contracts can't mint in random mode!"
+            );
+        }
+
+        let remaining = self.total_tokens_left();
+        require!(remaining > 0, "All tokens have been minted already!");
+
+        // the source is seeded from the block random seed, which everyone in
+        // the same block knows and validators can influence, so a draw is
+        // predictable within a block: fine to shuffle the mint order, not
+        // enough to protect anything worth more than the mint price
+        let mut rand_source = RandomnessSource::<Self::Api>::new();
+        let position = rand_source.next_u32_in_range(0, remaining);
+        let last_position = remaining - 1;
+
+        let index_to_mint = self.unminted_index_at(position);
+
+        if position != last_position {
+            let last_index = self.unminted_index_at(last_position);
+            self.unminted_indexes(position).set(&last_index);
+        }
+        self.unminted_indexes(last_position).clear();
+
+        index_to_mint
+    }
+
+    fn unminted_index_at(&self, position: u32) -> u32 {
+        let index = self.unminted_indexes(position).get();
+
+        if index == 0 {
+            position + 1
+        } else {
+            index
+        }
+    }
+
     fn handle_next_index_setup(&self) {
         let minted_index = self.next_index_to_mint().get();
         let drop_amount = self.amount_of_tokens_per_drop().get();
@@ -817,15 +2312,16 @@ pub trait ElvenTools {
             }
         }
 
-        let next_index = minted_index + 1;
-        self.next_index_to_mint().set(&next_index);
+        if !self.random_mint().get() {
+            let next_index = minted_index + 1;
+            self.next_index_to_mint().set(&next_index);
+        }
     }
 
-    fn build_token_name(&self) -> ManagedBuffer {
+    fn build_token_name(&self, current_index: u32) -> ManagedBuffer {
         // use alloc::string::ToString;
 
         let token_name = self.nft_token_name().get();
-        let current_index = self.next_index_to_mint().get();
 
         let token_index = self.decimal_to_ascii(current_index);
         let hash_sign = ManagedBuffer::new_from_bytes(AFTER_NAME_BEFORE_NUMBER);
@@ -839,11 +2335,9 @@ pub trait ElvenTools {
         full_token_name
     }
 
-    fn build_uris_vec(&self) -> ManagedVec<ManagedBuffer> {
+    fn build_uris_vec(&self, index_to_mint: u32) -> ManagedVec<ManagedBuffer> {
         // use alloc::string::ToString;
 
-        let index_to_mint = self.next_index_to_mint().get();
-
         let mut uris = ManagedVec::new();
 
         let cid = self.full_base_cid().get();
@@ -880,9 +2374,8 @@ pub trait ElvenTools {
     }
 
     // This can be probably optimized with attributes struct, had problems with decoding on the api side
-    fn build_attributes_buffer(&self) -> ManagedBuffer {
+    fn build_attributes_buffer(&self, index_to_mint: u32) -> ManagedBuffer {
 
-        let index_to_mint = self.next_index_to_mint().get();
         let metadata_key_name = ManagedBuffer::new_from_bytes(METADATA_KEY_NAME);
         let metadata_index_file = self.decimal_to_ascii(index_to_mint);
         let metadata_file_extension = ManagedBuffer::new_from_bytes(METADATA_FILE_EXTENSION);
@@ -908,6 +2401,52 @@ pub trait ElvenTools {
     // drop / limiting utils methods
     // -----------------------
 
+    fn require_presale_mint_allowed(&self, tokens: u32) {
+        // require: presale should be open
+        let active_phase = self.require_presale_open();
+
+        // require: drop should be set
+        require!(
+            !self.amount_of_tokens_per_drop().is_empty(),
+            "Drop is not set!"
+        );
+
+        // require: tokens limit should be set
+        require!(
+            !self.tokens_limit_per_presale_transaction().is_empty(),
+            "Tokens limit per transaction is not set!"
+        );
+
+        // require: Token should be issued
+        require!(!self.nft_token_id().is_empty(), "Token not issued!");
+
+        let token = self.nft_token_id().get();
+        let roles = self.blockchain().get_esdt_local_roles(&token);
+
+        // require: ESDTNFTCreate Role
+        require!(
+            roles.has_role(&EsdtLocalRole::NftCreate),
+            "ESDTNFTCreate role not set!"
+        );
+
+        // require: Tokens should be lower than max_tokens
+        require!(
+            tokens <= self.tokens_limit_per_presale_transaction().get(),
+            "The number of tokens to mint should be lower than the max tokens per transaction!"
+        );
+
+        // require: There should be enough tokens left to mint in this drop
+        require!(
+            self.get_current_left_tokens_amount() >= tokens,
+            "All tokens have been minted already for this presale!"
+        );
+
+        // require: the phase wallet limit holds on every presale path
+        if let Some((phase_id, phase)) = active_phase {
+            self.track_phase_mints(phase_id, &phase, tokens);
+        }
+    }
+
     #[view(getCurrentLeftTokensAmount)]
     fn get_current_left_tokens_amount(&self) -> u32 {
         let drop_amount = self.amount_of_tokens_per_drop().get();
@@ -986,6 +2525,137 @@ pub trait ElvenTools {
     // view methods
     // -----------------------
 
+    // what the address can still mint in the current drop, u32::MAX when
+    // there are no wallet limits
+    #[view(getRemainingAllowance)]
+    fn get_remaining_allowance(&self, address: ManagedAddress, is_presale: bool) -> u32 {
+        let mut remaining = u32::MAX;
+
+        let per_drop_limit = self.wallet_mint_limit_per_drop(is_presale).get();
+        if per_drop_limit > 0 {
+            let minted = self
+                .minted_by_wallet_per_drop(is_presale, self.opened_drop().get(), &address)
+                .get();
+
+            remaining = per_drop_limit.saturating_sub(minted);
+        }
+
+        let total_limit = self.wallet_mint_limit(is_presale).get();
+        if total_limit > 0 {
+            let minted = self.minted_by_wallet(is_presale, &address).get();
+
+            remaining = core::cmp::min(remaining, total_limit.saturating_sub(minted));
+        }
+
+        remaining
+    }
+
+    // phases that haven't ended yet, the active one included
+    #[view(getActiveAndUpcomingPhases)]
+    fn get_active_and_upcoming_phases(
+        &self,
+    ) -> MultiValueEncoded<MultiValue2<u64, SalePhase<Self::Api>>> {
+        let now = self.blockchain().get_block_timestamp();
+        let mut result = MultiValueEncoded::new();
+
+        for (phase_id, phase) in self.sale_phases().iter() {
+            if phase.end_timestamp > now {
+                result.push((phase_id, phase).into());
+            }
+        }
+
+        result
+    }
+
+    #[view(getActivePhase)]
+    fn get_active_phase_view(&self) -> OptionalValue<MultiValue2<u64, SalePhase<Self::Api>>> {
+        match self.get_active_phase() {
+            Some(active_phase) => OptionalValue::Some(active_phase.into()),
+            None => OptionalValue::None,
+        }
+    }
+
+    // live price and seconds until the next price drop, 0 once at the floor
+    #[view(getDutchAuctionPrice)]
+    fn get_dutch_auction_price(&self) -> MultiValue2<BigUint, u64> {
+        require!(!self.dutch_auction().is_empty(), "Auction is not set!");
+
+        let auction = self.dutch_auction().get();
+        let price = self.get_auction_price(&auction);
+        let now = self.blockchain().get_block_timestamp();
+
+        let next_drop_in = if price == auction.floor_price {
+            0
+        } else if now < auction.start_timestamp {
+            auction.start_timestamp - now + auction.step_interval
+        } else {
+            auction.step_interval - (now - auction.start_timestamp) % auction.step_interval
+        };
+
+        (price, next_drop_in).into()
+    }
+
+    #[view(getAuctionRebate)]
+    fn get_auction_rebate(&self, auction_id: u64, address: ManagedAddress) -> BigUint {
+        let paid = self.auction_paid(auction_id, &address).get();
+        let minted = self.auction_minted(auction_id, &address).get();
+
+        let clearing_price = self.auction_clearing_price(auction_id).get();
+
+        paid - clearing_price * BigUint::from(minted)
+    }
+
+    #[view(getMintCost)]
+    fn get_mint_cost_view(&self, tokens: u32) -> BigUint {
+        self.get_mint_cost(&TokenIdentifier::egld(), 0, tokens)
+    }
+
+    #[view(getPaymentPrices)]
+    fn get_payment_prices(&self) -> MultiValueEncoded<MultiValue2<TokenIdentifier, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+
+        for (token, price) in self.payment_prices().iter() {
+            result.push((token, price).into());
+        }
+
+        result
+    }
+
+    #[view(getPresalePaymentPrices)]
+    fn get_presale_payment_prices(
+        &self,
+    ) -> MultiValueEncoded<MultiValue2<TokenIdentifier, BigUint>> {
+        let mut result = MultiValueEncoded::new();
+
+        for (token, price) in self.presale_payment_prices().iter() {
+            result.push((token, price).into());
+        }
+
+        result
+    }
+
+    #[view(getMarketplaces)]
+    fn get_marketplaces(&self) -> MultiValueEncoded<MultiValue2<ManagedAddress, ManagedBuffer>> {
+        let mut result = MultiValueEncoded::new();
+
+        for (marketplace, claim_function) in self.marketplaces().iter() {
+            result.push((marketplace, claim_function).into());
+        }
+
+        result
+    }
+
+    #[view(getPayees)]
+    fn get_payees(&self) -> MultiValueEncoded<MultiValue2<ManagedAddress, u32>> {
+        let mut result = MultiValueEncoded::new();
+
+        for (payee, share) in self.payees().iter() {
+            result.push((payee, share).into());
+        }
+
+        result
+    }
+
     #[view(getNftTokenId)]
     #[storage_mapper("nftTokenId")]
     fn nft_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
@@ -1035,6 +2705,13 @@ pub trait ElvenTools {
     #[storage_mapper("nextIndexToMint")]
     fn next_index_to_mint(&self) -> SingleValueMapper<u32>;
 
+    #[view(isRandomMint)]
+    #[storage_mapper("randomMint")]
+    fn random_mint(&self) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("unmintedIndexes")]
+    fn unminted_indexes(&self, position: u32) -> SingleValueMapper<u32>;
+
     #[view(getRoyalties)]
     #[storage_mapper("royalties")]
     fn royalties(&self) -> SingleValueMapper<BigUint>;
@@ -1061,4 +2738,199 @@ pub trait ElvenTools {
 
     #[storage_mapper("isWhitelistedPerDrop")]
     fn is_whitelisted_per_drop(&self, id: u16) -> MapMapper<ManagedAddress, bool>;
+
+    #[storage_mapper("payees")]
+    fn payees(&self) -> MapMapper<ManagedAddress, u32>;
+
+    #[view(getAccumulatePayouts)]
+    #[storage_mapper("accumulatePayouts")]
+    fn accumulate_payouts(&self) -> SingleValueMapper<bool>;
+
+    #[view(getPayoutBalance)]
+    #[storage_mapper("payoutBalance")]
+    fn payout_balance(
+        &self,
+        payee: &ManagedAddress,
+        token: &TokenIdentifier,
+    ) -> SingleValueMapper<BigUint>;
+
+    #[view(getTotalPendingPayouts)]
+    #[storage_mapper("totalPendingPayouts")]
+    fn total_pending_payouts(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[view(getWalletMintLimitPerDrop)]
+    #[storage_mapper("walletMintLimitPerDrop")]
+    fn wallet_mint_limit_per_drop(&self, is_presale: bool) -> SingleValueMapper<u32>;
+
+    #[view(getWalletMintLimit)]
+    #[storage_mapper("walletMintLimit")]
+    fn wallet_mint_limit(&self, is_presale: bool) -> SingleValueMapper<u32>;
+
+    #[view(getMintedByWalletPerDrop)]
+    #[storage_mapper("mintedByWalletPerDrop")]
+    fn minted_by_wallet_per_drop(
+        &self,
+        is_presale: bool,
+        drop_id: u16,
+        address: &ManagedAddress,
+    ) -> SingleValueMapper<u32>;
+
+    #[view(getMintedByWallet)]
+    #[storage_mapper("mintedByWallet")]
+    fn minted_by_wallet(
+        &self,
+        is_presale: bool,
+        address: &ManagedAddress,
+    ) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("gatedCollections")]
+    fn gated_collections(&self) -> MapMapper<TokenIdentifier, u32>;
+
+    #[view(getPresaleDeposit)]
+    #[storage_mapper("presaleDeposit")]
+    fn presale_deposit(&self, address: &ManagedAddress) -> SingleValueMapper<BigUint>;
+
+    #[view(isGatedNonceUsed)]
+    #[storage_mapper("gatedNonceUsed")]
+    fn gated_nonce_used(
+        &self,
+        drop_id: u16,
+        collection: &TokenIdentifier,
+        nonce: u64,
+    ) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("whitelistTiers")]
+    fn whitelist_tiers(&self, drop_id: u16) -> MapMapper<u32, WhitelistTier<Self::Api>>;
+
+    #[view(getWhitelistTiersByAddress)]
+    #[storage_mapper("whitelistTiersByAddress")]
+    fn whitelist_tiers_by_address(
+        &self,
+        drop_id: u16,
+        address: &ManagedAddress,
+    ) -> UnorderedSetMapper<u32>;
+
+    #[view(getMintedPerWhitelistTier)]
+    #[storage_mapper("mintedPerWhitelistTier")]
+    fn minted_per_whitelist_tier(
+        &self,
+        drop_id: u16,
+        tier_id: u32,
+        address: &ManagedAddress,
+    ) -> SingleValueMapper<u32>;
+
+    #[view(getMerkleRoot)]
+    #[storage_mapper("merkleRoot")]
+    fn merkle_root(&self, drop_id: u16) -> SingleValueMapper<ManagedByteArray<Self::Api, 32>>;
+
+    #[view(getMerkleHash)]
+    #[storage_mapper("merkleHash")]
+    fn merkle_hash(&self, drop_id: u16) -> SingleValueMapper<MerkleHash>;
+
+    #[view(getMerkleTierPrice)]
+    #[storage_mapper("merkleTierPrice")]
+    fn merkle_tier_price(&self, drop_id: u16, price_tier: u32) -> SingleValueMapper<BigUint>;
+
+    #[view(getMerkleMinted)]
+    #[storage_mapper("merkleMinted")]
+    fn merkle_minted(&self, drop_id: u16, address: &ManagedAddress) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("salePhases")]
+    fn sale_phases(&self) -> MapMapper<u64, SalePhase<Self::Api>>;
+
+    #[storage_mapper("lastSalePhaseId")]
+    fn last_sale_phase_id(&self) -> SingleValueMapper<u64>;
+
+    #[view(getMintedPerPhase)]
+    #[storage_mapper("mintedPerPhase")]
+    fn minted_per_phase(&self, phase_id: u64, address: &ManagedAddress) -> SingleValueMapper<u32>;
+
+    #[view(getDutchAuction)]
+    #[storage_mapper("dutchAuction")]
+    fn dutch_auction(&self) -> SingleValueMapper<DutchAuction<Self::Api>>;
+
+    #[storage_mapper("lastAuctionId")]
+    fn last_auction_id(&self) -> SingleValueMapper<u64>;
+
+    #[view(getAuctionClearingPrice)]
+    #[storage_mapper("auctionClearingPrice")]
+    fn auction_clearing_price(&self, auction_id: u64) -> SingleValueMapper<BigUint>;
+
+    #[view(getAuctionEnded)]
+    #[storage_mapper("auctionEnded")]
+    fn auction_ended(&self, auction_id: u64) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("auctionTotalMinted")]
+    fn auction_total_minted(&self, auction_id: u64) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("auctionPaid")]
+    fn auction_paid(&self, auction_id: u64, address: &ManagedAddress)
+        -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("auctionMinted")]
+    fn auction_minted(&self, auction_id: u64, address: &ManagedAddress) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("auctionHeldFunds")]
+    fn auction_held_funds(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getPriceSchedule)]
+    #[storage_mapper("priceSchedule")]
+    fn price_schedule(&self) -> VecMapper<PriceTier<Self::Api>>;
+
+    #[storage_mapper("paymentPrices")]
+    fn payment_prices(&self) -> MapMapper<TokenIdentifier, BigUint>;
+
+    #[storage_mapper("presalePaymentPrices")]
+    fn presale_payment_prices(&self) -> MapMapper<TokenIdentifier, BigUint>;
+
+    #[view(getRevenue)]
+    #[storage_mapper("revenueByToken")]
+    fn revenue_by_token(&self, token: &TokenIdentifier) -> SingleValueMapper<BigUint>;
+
+    #[view(getStakingContract)]
+    #[storage_mapper("stakingContract")]
+    fn staking_contract(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getStakerFreeMintsPerNft)]
+    #[storage_mapper("stakerFreeMintsPerNft")]
+    fn staker_free_mints_per_nft(&self) -> SingleValueMapper<u32>;
+
+    #[view(getStakerDiscount)]
+    #[storage_mapper("stakerDiscount")]
+    fn staker_discount(&self) -> SingleValueMapper<u32>;
+
+    #[view(getStakedNftMints)]
+    #[storage_mapper("stakedNftMints")]
+    fn staked_nft_mints(&self, drop_id: u16, nonce: u64) -> SingleValueMapper<u32>;
+
+    #[view(getStakingRewardShare)]
+    #[storage_mapper("stakingRewardShare")]
+    fn staking_reward_share(&self) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("marketplaces")]
+    fn marketplaces(&self) -> MapMapper<ManagedAddress, ManagedBuffer>;
+
+    #[view(getRoyaltiesTreasury)]
+    #[storage_mapper("royaltiesTreasury")]
+    fn royalties_treasury(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getForwardRoyaltiesToSplits)]
+    #[storage_mapper("forwardRoyaltiesToSplits")]
+    fn forward_royalties_to_splits(&self) -> SingleValueMapper<bool>;
+
+    #[event("royaltiesClaimed")]
+    fn royalties_claimed_event(
+        &self,
+        #[indexed] marketplace: &ManagedAddress,
+        #[indexed] token: &TokenIdentifier,
+        receiver: &ManagedAddress,
+    );
+
+    #[event("royaltiesClaimFailed")]
+    fn royalties_claim_failed_event(
+        &self,
+        #[indexed] marketplace: &ManagedAddress,
+        #[indexed] token: &TokenIdentifier,
+        err_msg: &ManagedBuffer,
+    );
 }
@@ -0,0 +1,17 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Copy)]
+pub enum MerkleHash {
+    Sha256,
+    Keccak256,
+}
+
+// the leaf is hash(address) when both the quantity and the price tier are 0,
+// hash(address ++ quantity ++ price tier) otherwise, numbers as 4 bytes big endian
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub struct MerkleProof<M: ManagedTypeApi> {
+    pub path: ManagedVec<M, ManagedByteArray<M, 32>>,
+    pub allowed_quantity: u32,
+    pub price_tier: u32,
+}
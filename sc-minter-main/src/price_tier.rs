@@ -0,0 +1,9 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub struct PriceTier<M: ManagedTypeApi> {
+    pub from_index: u32,
+    pub price: BigUint<M>,
+    pub increment: BigUint<M>,
+}
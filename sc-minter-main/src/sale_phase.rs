@@ -0,0 +1,12 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub struct SalePhase<M: ManagedTypeApi> {
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+    pub price: BigUint<M>,
+    pub whitelist_required: bool,
+    // 0 means no limit
+    pub wallet_limit: u32,
+}
@@ -0,0 +1,17 @@
+elrond_wasm::imports!();
+
+#[elrond_wasm::proxy]
+pub trait StakingContract {
+    #[payable("EGLD")]
+    #[endpoint(depositReward)]
+    fn deposit_reward(&self);
+
+    #[view(getStakedNfts)]
+    fn get_staked_nfts(&self, address: ManagedAddress) -> MultiValueEncoded<u64>;
+
+    #[view(getReceiptTokenId)]
+    fn get_receipt_token_id(&self) -> TokenIdentifier;
+
+    #[view(getNftByReceipt)]
+    fn get_nft_by_receipt(&self, receipt_nonce: u64) -> u64;
+}
@@ -0,0 +1,9 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub enum TimelockAction<M: ManagedTypeApi> {
+    SetCid(ManagedBuffer<M>),
+    SetRoyalties(BigUint<M>),
+    SetTotalySupply(u32),
+}
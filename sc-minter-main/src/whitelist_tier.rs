@@ -0,0 +1,11 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub struct WhitelistTier<M: ManagedTypeApi> {
+    pub price: BigUint<M>,
+    // 0 means no limit
+    pub allowance: u32,
+    // 0 means the tier is open with the presale
+    pub start_timestamp: u64,
+}
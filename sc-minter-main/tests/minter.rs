@@ -0,0 +1,1871 @@
+use deainostri_nft_minter::{merkle_proof::MerkleProof, *};
+
+use elrond_wasm::{
+    contract_base::ContractBase,
+    elrond_codec::multi_types::{IgnoreValue, OptionalValue},
+    types::{
+        Address, EsdtLocalRole, ManagedAsyncCallError, ManagedAsyncCallResult, ManagedByteArray,
+        ManagedVec, MultiValueEncoded,
+    },
+};
+
+use elrond_wasm_debug::{
+    managed_address, managed_biguint, managed_buffer, managed_token_id, rust_biguint,
+    testing_framework::*, tx_mock::TxInputESDT, DebugApi,
+};
+
+const WASM_PATH: &'static str = "output/deainostri-nft-minter.wasm";
+const NFT_TOKEN_ID: &[u8] = b"DEAN-123456";
+
+struct MinterContext<SCObjBuilder>
+where
+    SCObjBuilder: 'static + Copy + Fn() -> deainostri_nft_minter::ContractObj<DebugApi>,
+{
+    pub blockchain_wrapper: BlockchainStateWrapper,
+    pub owner_address: Address,
+
+    pub alice: Address,
+    pub bob: Address,
+
+    pub sc_wrapper: ContractObjWrapper<deainostri_nft_minter::ContractObj<DebugApi>, SCObjBuilder>,
+}
+
+fn setup_minter<SCObjBuilder>(
+    sc_builder: SCObjBuilder,
+    amount_of_tokens: u32,
+) -> MinterContext<SCObjBuilder>
+where
+    SCObjBuilder: 'static + Copy + Fn() -> deainostri_nft_minter::ContractObj<DebugApi>,
+{
+    let rust_zero = rust_biguint!(0u64);
+    let mut blockchain_wrapper = BlockchainStateWrapper::new();
+
+    // -----------------------
+    // create addresses
+    // -----------------------
+
+    let owner_address = blockchain_wrapper.create_user_account(&rust_zero);
+    let alice = blockchain_wrapper.create_user_account(&rust_biguint!(10_000u64));
+    let bob = blockchain_wrapper.create_user_account(&rust_biguint!(10_000u64));
+
+    // -----------------------
+    // create sc wrapper
+    // -----------------------
+
+    let sc_wrapper = blockchain_wrapper.create_sc_account(
+        &rust_zero,
+        Some(&owner_address),
+        sc_builder,
+        WASM_PATH,
+    );
+
+    blockchain_wrapper.set_esdt_local_roles(
+        sc_wrapper.address_ref(),
+        NFT_TOKEN_ID,
+        &[EsdtLocalRole::NftCreate],
+    );
+
+    // -----------------------
+    // init sc and open the public sale
+    // -----------------------
+
+    blockchain_wrapper
+        .execute_tx(&owner_address, &sc_wrapper, &rust_zero, |sc| {
+            sc.init(
+                managed_buffer!(b"cid"),
+                managed_buffer!(b"Dean"),
+                amount_of_tokens,
+                managed_biguint!(500u64),
+                managed_biguint!(100u64),
+                managed_biguint!(50u64),
+                OptionalValue::None,
+                OptionalValue::None,
+            );
+
+            sc.nft_token_id().set(&managed_token_id!(NFT_TOKEN_ID));
+            sc.set_drop(amount_of_tokens);
+            sc.set_tokens_limit_per_sale_transaction(amount_of_tokens);
+            sc.set_tokens_limit_per_presale_transaction(amount_of_tokens);
+            sc.start_minting();
+        })
+        .assert_ok();
+
+    MinterContext {
+        blockchain_wrapper,
+        owner_address,
+        alice,
+        bob,
+        sc_wrapper,
+    }
+}
+
+#[test]
+fn payees_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+    let bob = &setup.bob;
+    let carol = b_wrapper.create_user_account(&rust_biguint!(10_000u64));
+
+    // -----------------------
+    // payments are forwarded, rounding leftovers go to the last payee
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut payees = MultiValueEncoded::new();
+            payees.push((managed_address!(alice), 3_333u32).into());
+            payees.push((managed_address!(bob), 6_667u32).into());
+
+            sc.set_payees(payees);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&carol, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(10_033u64));
+    b_wrapper.check_egld_balance(bob, &rust_biguint!(10_067u64));
+
+    // -----------------------
+    // accumulated payouts wait in the contract
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_accumulate_payouts(true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&carol, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+
+            assert_eq!(
+                sc.payout_balance(&managed_address!(alice), &managed_token_id!(b"EGLD"))
+                    .get(),
+                managed_biguint!(33u64)
+            );
+            assert_eq!(
+                sc.payout_balance(&managed_address!(bob), &managed_token_id!(b"EGLD"))
+                    .get(),
+                managed_biguint!(67u64)
+            );
+            assert_eq!(
+                sc.total_pending_payouts(&managed_token_id!(b"EGLD")).get(),
+                managed_biguint!(100u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(10_033u64));
+    b_wrapper.check_egld_balance(sc.address_ref(), &rust_biguint!(100u64));
+
+    // -----------------------
+    // resetting the payees keeps what they are owed
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_payees(MultiValueEncoded::new());
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&carol, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(100u64));
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_payout(OptionalValue::None);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_payout(OptionalValue::None);
+        })
+        .assert_user_error("Nothing to withdraw!");
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_payout(OptionalValue::None);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(10_066u64));
+    b_wrapper.check_egld_balance(bob, &rust_biguint!(10_134u64));
+    b_wrapper.check_egld_balance(sc.address_ref(), &rust_biguint!(0u64));
+}
+
+#[test]
+fn marketplace_registry_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+
+    let marketplace = b_wrapper.create_sc_account(
+        &rust_biguint!(0u64),
+        Some(owner),
+        deainostri_nft_minter::contract_obj,
+        WASM_PATH,
+    );
+
+    // -----------------------
+    // only contracts can be registered
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.add_marketplace(managed_address!(alice), managed_buffer!(b"claimRoyalties"));
+        })
+        .assert_user_error("Invalid marketplace!");
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.add_marketplace(
+                managed_address!(marketplace.address_ref()),
+                managed_buffer!(b"claimRoyalties"),
+            );
+
+            assert_eq!(sc.get_marketplaces().len(), 1);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // claims need a registered marketplace and a receiver
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_tokens(
+                managed_address!(alice),
+                managed_token_id!(b"EGLD"),
+                1,
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Marketplace not registered!");
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_tokens(
+                managed_address!(marketplace.address_ref()),
+                managed_token_id!(b"EGLD"),
+                1,
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("No receiver set for the claimed royalties!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_tokens(
+                managed_address!(marketplace.address_ref()),
+                managed_token_id!(b"EGLD"),
+                1,
+                OptionalValue::Some(managed_address!(alice)),
+            );
+        })
+        .assert_user_error("Caller does not have the required role!");
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.remove_marketplace(managed_address!(marketplace.address_ref()));
+
+            assert_eq!(sc.get_marketplaces().len(), 0);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.remove_marketplace(managed_address!(marketplace.address_ref()));
+        })
+        .assert_user_error("Marketplace not registered!");
+}
+
+#[test]
+fn claim_tokens_callback_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+    let bob = &setup.bob;
+    let carol = b_wrapper.create_user_account(&rust_biguint!(10_000u64));
+    let sc_address = sc.address_ref().clone();
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut payees = MultiValueEncoded::new();
+            payees.push((managed_address!(alice), 5_000u32).into());
+            payees.push((managed_address!(bob), 5_000u32).into());
+
+            sc.set_payees(payees);
+            sc.set_forward_royalties_to_splits(true);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // royalties claimed to the contract go through the splits, the
+    // callback runs with the royalties as payment
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&carol, &sc, &rust_biguint!(100u64), |sc| {
+            sc.claim_tokens_callback(
+                managed_address!(alice),
+                managed_token_id!(b"EGLD"),
+                managed_address!(&sc_address),
+                ManagedAsyncCallResult::Ok(IgnoreValue),
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(10_050u64));
+    b_wrapper.check_egld_balance(bob, &rust_biguint!(10_050u64));
+
+    // -----------------------
+    // a failed claim splits nothing
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_tokens_callback(
+                managed_address!(alice),
+                managed_token_id!(b"EGLD"),
+                managed_address!(&sc_address),
+                ManagedAsyncCallResult::Err(ManagedAsyncCallError {
+                    err_code: 4,
+                    err_msg: managed_buffer!(b"claim failed"),
+                }),
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(10_050u64));
+    b_wrapper.check_egld_balance(sc.address_ref(), &rust_biguint!(0u64));
+}
+
+const USDC_TOKEN_ID: &[u8] = b"USDC-123456";
+
+#[test]
+fn esdt_payment_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+    let bob = &setup.bob;
+
+    b_wrapper.set_esdt_balance(alice, USDC_TOKEN_ID, &rust_biguint!(1_000u64));
+    b_wrapper.set_esdt_balance(alice, b"WRONG-123456", &rust_biguint!(1_000u64));
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_payment_price(managed_token_id!(USDC_TOKEN_ID), managed_biguint!(20u64));
+            sc.set_presale_payment_price(managed_token_id!(USDC_TOKEN_ID), managed_biguint!(10u64));
+            sc.approve_whitelist_per_drop(managed_address!(alice));
+            sc.resume_presale();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // listed tokens pay their own sale and presale prices
+    // -----------------------
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, USDC_TOKEN_ID, 0, &rust_biguint!(40u64), |sc| {
+            sc.mint(
+                managed_token_id!(USDC_TOKEN_ID),
+                0,
+                managed_biguint!(40u64),
+                OptionalValue::Some(2),
+            );
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, USDC_TOKEN_ID, 0, &rust_biguint!(20u64), |sc| {
+            sc.mint(
+                managed_token_id!(USDC_TOKEN_ID),
+                0,
+                managed_biguint!(20u64),
+                OptionalValue::Some(2),
+            );
+        })
+        .assert_user_error("Invalid amount as payment");
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, USDC_TOKEN_ID, 0, &rust_biguint!(10u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(USDC_TOKEN_ID),
+                0,
+                managed_biguint!(10u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(
+            alice,
+            &sc,
+            b"WRONG-123456",
+            0,
+            &rust_biguint!(20u64),
+            |sc| {
+                sc.mint(
+                    managed_token_id!(b"WRONG-123456"),
+                    0,
+                    managed_biguint!(20u64),
+                    OptionalValue::Some(1),
+                );
+            },
+        )
+        .assert_user_error("Payment token not accepted!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(owner, USDC_TOKEN_ID, &rust_biguint!(50u64));
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(100u64));
+
+    // -----------------------
+    // revenue and payouts are kept per token
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut payees = MultiValueEncoded::new();
+            payees.push((managed_address!(bob), 10_000u32).into());
+
+            sc.set_payees(payees);
+            sc.set_accumulate_payouts(true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, USDC_TOKEN_ID, 0, &rust_biguint!(20u64), |sc| {
+            sc.mint(
+                managed_token_id!(USDC_TOKEN_ID),
+                0,
+                managed_biguint!(20u64),
+                OptionalValue::Some(1),
+            );
+
+            assert_eq!(
+                sc.revenue_by_token(&managed_token_id!(USDC_TOKEN_ID)).get(),
+                managed_biguint!(70u64)
+            );
+            assert_eq!(
+                sc.revenue_by_token(&managed_token_id!(b"EGLD")).get(),
+                managed_biguint!(100u64)
+            );
+            assert_eq!(
+                sc.payout_balance(&managed_address!(bob), &managed_token_id!(USDC_TOKEN_ID))
+                    .get(),
+                managed_biguint!(20u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_payout(OptionalValue::None);
+        })
+        .assert_user_error("Nothing to withdraw!");
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_payout(OptionalValue::Some(managed_token_id!(USDC_TOKEN_ID)));
+        })
+        .assert_ok();
+
+    b_wrapper.check_esdt_balance(bob, USDC_TOKEN_ID, &rust_biguint!(20u64));
+}
+
+#[test]
+fn dutch_auction_rebate_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+    let bob = &setup.bob;
+
+    // -----------------------
+    // from 300 down to 100, 50 less every 100 seconds
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_dutch_auction(
+                managed_biguint!(300u64),
+                managed_biguint!(100u64),
+                managed_biguint!(50u64),
+                100,
+                1_000,
+                OptionalValue::Some(true),
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(1_000);
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(300u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(300u64),
+                OptionalValue::Some(1),
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(1_200);
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(400u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(400u64),
+                OptionalValue::Some(2),
+            );
+
+            assert_eq!(sc.auction_clearing_price(1).get(), managed_biguint!(200u64));
+            assert_eq!(sc.auction_held_funds().get(), managed_biguint!(400u64));
+        })
+        .assert_ok();
+
+    // -----------------------
+    // only the floor price is paid out before the auction ends
+    // -----------------------
+
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(300u64));
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_auction_rebate(1);
+        })
+        .assert_user_error("The auction has not ended yet!");
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.end_dutch_auction();
+
+            assert_eq!(sc.auction_held_funds().get(), managed_biguint!(100u64));
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(600u64));
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(300u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+        })
+        .assert_user_error("The auction has ended!");
+
+    // -----------------------
+    // everyone ends up paying the clearing price of 200
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_auction_rebate(1);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_auction_rebate(1);
+        })
+        .assert_user_error("Nothing to claim!");
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_auction_rebate(1);
+        })
+        .assert_user_error("Nothing to claim!");
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(9_800u64));
+    b_wrapper.check_egld_balance(bob, &rust_biguint!(9_600u64));
+    b_wrapper.check_egld_balance(sc.address_ref(), &rust_biguint!(0u64));
+}
+
+#[test]
+fn dutch_auction_without_mints_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_dutch_auction(
+                managed_biguint!(300u64),
+                managed_biguint!(100u64),
+                managed_biguint!(50u64),
+                100,
+                1_000,
+                OptionalValue::Some(true),
+            );
+            sc.end_dutch_auction();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.end_dutch_auction();
+        })
+        .assert_user_error("The auction has ended!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim_auction_rebate(1);
+        })
+        .assert_user_error("Nothing to claim!");
+
+    // -----------------------
+    // a settled auction can be replaced
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_dutch_auction(
+                managed_biguint!(200u64),
+                managed_biguint!(100u64),
+                managed_biguint!(50u64),
+                100,
+                1_000,
+                OptionalValue::None,
+            );
+
+            assert_eq!(sc.dutch_auction().get().id, 2);
+            assert_eq!(sc.auction_held_funds().get(), managed_biguint!(0u64));
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(0u64));
+}
+
+#[test]
+fn dutch_auction_esdt_prices_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+
+    // -----------------------
+    // the auction prices EGLD only, ESDT prices can't run next to it
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_payment_price(managed_token_id!(USDC_TOKEN_ID), managed_biguint!(20u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_dutch_auction(
+                managed_biguint!(300u64),
+                managed_biguint!(100u64),
+                managed_biguint!(50u64),
+                100,
+                1_000,
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("The dutch auction only accepts EGLD, remove the ESDT prices first!");
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.remove_payment_price(managed_token_id!(USDC_TOKEN_ID));
+            sc.set_dutch_auction(
+                managed_biguint!(300u64),
+                managed_biguint!(100u64),
+                managed_biguint!(50u64),
+                100,
+                1_000,
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_payment_price(managed_token_id!(USDC_TOKEN_ID), managed_biguint!(20u64));
+        })
+        .assert_user_error("The dutch auction only accepts EGLD!");
+}
+
+#[test]
+fn price_schedule_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+
+    // -----------------------
+    // 100 plus 10 per token for the first 3, then a flat 200
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push((0u32, managed_biguint!(100u64), managed_biguint!(10u64)).into());
+            tiers.push((3u32, managed_biguint!(200u64), managed_biguint!(0u64)).into());
+
+            sc.set_price_schedule(tiers);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+        })
+        .assert_ok();
+
+    // -----------------------
+    // indexes 1 and 2 are in the first tier, 3 and 4 in the second
+    // -----------------------
+
+    b_wrapper
+        .execute_query(&sc, |sc| {
+            assert_eq!(sc.get_mint_cost_view(4), managed_biguint!(630u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(600u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(600u64),
+                OptionalValue::Some(4),
+            );
+        })
+        .assert_user_error("Invalid amount as payment");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(630u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(630u64),
+                OptionalValue::Some(4),
+            );
+
+            assert_eq!(sc.minted_indexes_total().get(), 5);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(730u64));
+}
+
+#[test]
+fn presale_exact_payment_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.approve_whitelist_per_drop(managed_address!(alice));
+            sc.resume_presale();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // 2 presale mints cost exactly 100, leftovers are not accepted
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(101u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(101u64),
+                OptionalValue::Some(2),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Invalid amount as payment");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(2),
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(100u64));
+}
+
+#[test]
+fn presale_phase_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+    let bob = &setup.bob;
+
+    // -----------------------
+    // a whitelist phase followed by a public one
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.add_sale_phase(100, 200, managed_biguint!(80u64), true, 0);
+            sc.add_sale_phase(200, 300, managed_biguint!(100u64), false, 0);
+            sc.approve_whitelist_per_drop(managed_address!(alice));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(80u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(80u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("No presale phase is active!");
+
+    b_wrapper.set_block_timestamp(150);
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(80u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(80u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(80u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(80u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Caller is not whitelisted!");
+
+    b_wrapper.set_block_timestamp(250);
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(80u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(80u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("No presale phase is active!");
+}
+
+#[test]
+fn presale_phase_price_and_limit_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+    let bob = &setup.bob;
+
+    b_wrapper.set_nft_balance(
+        alice,
+        GATE_TOKEN_ID,
+        1,
+        &rust_biguint!(1u64),
+        &rust_biguint!(1u64),
+    );
+    b_wrapper.set_nft_balance(
+        bob,
+        GATE_TOKEN_ID,
+        2,
+        &rust_biguint!(1u64),
+        &rust_biguint!(1u64),
+    );
+
+    // -----------------------
+    // the whitelist phase sells at 80 while the presale price is 50
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.add_sale_phase(100, 200, managed_biguint!(80u64), true, 2);
+            sc.approve_whitelist_per_drop(managed_address!(alice));
+            sc.set_gated_collection(managed_token_id!(GATE_TOKEN_ID), 2);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(150);
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(50u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(50u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Invalid amount as payment");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(80u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(80u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    // -----------------------
+    // gated mints pay the phase price and count for its wallet limit
+    // -----------------------
+
+    let gate_transfer = |nonce: u64| {
+        [TxInputESDT {
+            token_identifier: GATE_TOKEN_ID.to_vec(),
+            nonce,
+            value: rust_biguint!(1u64),
+        }]
+    };
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(160u64), |sc| {
+            sc.deposit_presale_payment(managed_biguint!(160u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_multi_transfer(alice, &sc, &gate_transfer(1), |sc| {
+            sc.mint_presale_with_nfts();
+        })
+        .assert_user_error("The wallet limit for this phase is reached!");
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(160u64), |sc| {
+            sc.deposit_presale_payment(managed_biguint!(160u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_multi_transfer(bob, &sc, &gate_transfer(2), |sc| {
+            sc.mint_presale_with_nfts();
+
+            assert_eq!(sc.minted_indexes_total().get(), 3);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(240u64));
+}
+
+#[test]
+fn wallet_mint_limits_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+
+    b_wrapper
+        .execute_query(&sc, |sc| {
+            assert_eq!(
+                sc.get_remaining_allowance(managed_address!(alice), false),
+                u32::MAX
+            );
+        })
+        .assert_ok();
+
+    // -----------------------
+    // sale: 2 per drop and 3 overall, presale: 1 per drop
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_wallet_mint_limits(false, 2, 3);
+            sc.set_wallet_mint_limits(true, 1, 0);
+            sc.approve_whitelist_per_drop(managed_address!(alice));
+            sc.resume_presale();
+
+            assert_eq!(
+                sc.get_remaining_allowance(managed_address!(alice), false),
+                2
+            );
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(200u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(200u64),
+                OptionalValue::Some(2),
+            );
+
+            assert_eq!(
+                sc.get_remaining_allowance(managed_address!(alice), false),
+                0
+            );
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+        })
+        .assert_user_error("The wallet mint limit is reached!");
+
+    // -----------------------
+    // presale mints are counted apart from sale mints
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(50u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(50u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+
+            assert_eq!(sc.get_remaining_allowance(managed_address!(alice), true), 0);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(50u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(50u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("The wallet mint limit is reached!");
+
+    // -----------------------
+    // a new drop resets the per drop caps, not the overall one
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_drop(5);
+            sc.approve_whitelist_per_drop(managed_address!(alice));
+
+            assert_eq!(
+                sc.get_remaining_allowance(managed_address!(alice), false),
+                1
+            );
+            assert_eq!(sc.get_remaining_allowance(managed_address!(alice), true), 1);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(200u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(200u64),
+                OptionalValue::Some(2),
+            );
+        })
+        .assert_user_error("The wallet mint limit is reached!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+
+            assert_eq!(
+                sc.get_remaining_allowance(managed_address!(alice), false),
+                0
+            );
+        })
+        .assert_ok();
+}
+
+fn merkle_leaf<SCObjBuilder>(
+    setup: &mut MinterContext<SCObjBuilder>,
+    address: &Address,
+    allowed_quantity: u32,
+    price_tier: u32,
+) -> [u8; 32]
+where
+    SCObjBuilder: 'static + Copy + Fn() -> deainostri_nft_minter::ContractObj<DebugApi>,
+{
+    let mut leaf = [0u8; 32];
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let mut data = managed_buffer!(address.as_bytes());
+            if allowed_quantity > 0 || price_tier > 0 {
+                data.append_bytes(&allowed_quantity.to_be_bytes());
+                data.append_bytes(&price_tier.to_be_bytes());
+            }
+
+            leaf = sc.crypto().sha256(&data).to_byte_array();
+        })
+        .assert_ok();
+
+    leaf
+}
+
+fn merkle_node<SCObjBuilder>(
+    setup: &mut MinterContext<SCObjBuilder>,
+    left: &[u8; 32],
+    right: &[u8; 32],
+) -> [u8; 32]
+where
+    SCObjBuilder: 'static + Copy + Fn() -> deainostri_nft_minter::ContractObj<DebugApi>,
+{
+    let (first, second) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    let mut node = [0u8; 32];
+
+    setup
+        .blockchain_wrapper
+        .execute_query(&setup.sc_wrapper, |sc| {
+            let mut data = managed_buffer!(first);
+            data.append_bytes(second);
+
+            node = sc.crypto().sha256(&data).to_byte_array();
+        })
+        .assert_ok();
+
+    node
+}
+
+fn merkle_proof(
+    sibling: &[u8; 32],
+    allowed_quantity: u32,
+    price_tier: u32,
+) -> OptionalValue<MerkleProof<DebugApi>> {
+    let mut path = ManagedVec::new();
+    path.push(ManagedByteArray::new_from_bytes(sibling));
+
+    OptionalValue::Some(MerkleProof {
+        path,
+        allowed_quantity,
+        price_tier,
+    })
+}
+
+#[test]
+fn merkle_whitelist_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+
+    let owner = setup.owner_address.clone();
+    let alice = setup.alice.clone();
+    let bob = setup.bob.clone();
+
+    // -----------------------
+    // alice may mint 2 at price tier 1, bob at the presale price
+    // -----------------------
+
+    let alice_leaf = merkle_leaf(&mut setup, &alice, 2, 1);
+    let bob_leaf = merkle_leaf(&mut setup, &bob, 0, 0);
+    let root = merkle_node(&mut setup, &alice_leaf, &bob_leaf);
+
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    b_wrapper.set_esdt_balance(&alice, USDC_TOKEN_ID, &rust_biguint!(1_000u64));
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let drop_id = sc.opened_drop().get();
+
+            sc.set_merkle_root(
+                drop_id,
+                ManagedByteArray::new_from_bytes(&root),
+                OptionalValue::None,
+            );
+            sc.set_merkle_tier_price(drop_id, 1, managed_biguint!(70u64));
+            sc.set_presale_payment_price(managed_token_id!(USDC_TOKEN_ID), managed_biguint!(5u64));
+            sc.resume_presale();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // a proof for someone else's leaf is rejected
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&bob, &sc, &rust_biguint!(140u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(140u64),
+                OptionalValue::Some(2),
+                merkle_proof(&bob_leaf, 2, 1),
+            );
+        })
+        .assert_user_error("Invalid merkle proof!");
+
+    // -----------------------
+    // the tier price is in EGLD, ESDT payments can't skip it
+    // -----------------------
+
+    b_wrapper
+        .execute_esdt_transfer(&alice, &sc, USDC_TOKEN_ID, 0, &rust_biguint!(10u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(USDC_TOKEN_ID),
+                0,
+                managed_biguint!(10u64),
+                OptionalValue::Some(2),
+                merkle_proof(&bob_leaf, 2, 1),
+            );
+        })
+        .assert_user_error("Payment token not accepted!");
+
+    b_wrapper
+        .execute_tx(&alice, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(2),
+                merkle_proof(&bob_leaf, 2, 1),
+            );
+        })
+        .assert_user_error("Invalid amount as payment");
+
+    b_wrapper
+        .execute_tx(&alice, &sc, &rust_biguint!(140u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(140u64),
+                OptionalValue::Some(2),
+                merkle_proof(&bob_leaf, 2, 1),
+            );
+        })
+        .assert_ok();
+
+    // -----------------------
+    // reusing the proof past its quantity is rejected
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&alice, &sc, &rust_biguint!(70u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(70u64),
+                OptionalValue::Some(1),
+                merkle_proof(&bob_leaf, 2, 1),
+            );
+        })
+        .assert_user_error("The whitelisted quantity is reached!");
+
+    // -----------------------
+    // an address only leaf pays the presale price, without a limit
+    // -----------------------
+
+    for _ in 0..2 {
+        b_wrapper
+            .execute_tx(&bob, &sc, &rust_biguint!(50u64), |sc| {
+                sc.mint_presale(
+                    managed_token_id!(b"EGLD"),
+                    0,
+                    managed_biguint!(50u64),
+                    OptionalValue::Some(1),
+                    merkle_proof(&alice_leaf, 0, 0),
+                );
+            })
+            .assert_ok();
+    }
+
+    b_wrapper.check_egld_balance(&owner, &rust_biguint!(240u64));
+}
+
+#[test]
+fn whitelist_management_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+    let bob = &setup.bob;
+    let carol = b_wrapper.create_user_account(&rust_biguint!(0u64));
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            let mut addresses = MultiValueEncoded::new();
+            addresses.push(managed_address!(alice));
+
+            sc.approve_whitelist_per_drop_many(addresses);
+        })
+        .assert_user_error("Caller does not have the required role!");
+
+    // -----------------------
+    // addresses are added in bulk and listed page by page
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut addresses = MultiValueEncoded::new();
+            addresses.push(managed_address!(alice));
+            addresses.push(managed_address!(bob));
+            addresses.push(managed_address!(&carol));
+
+            sc.approve_whitelist_per_drop_many(addresses);
+
+            assert_eq!(sc.get_whitelist_count_per_drop(1), 3);
+
+            let first_page = sc.get_whitelist_per_drop(1, 0, 2).to_vec();
+            assert_eq!(first_page.len(), 2);
+            assert_eq!(*first_page.get(0), managed_address!(alice));
+            assert_eq!(*first_page.get(1), managed_address!(bob));
+
+            let last_page = sc.get_whitelist_per_drop(1, 2, 2).to_vec();
+            assert_eq!(last_page.len(), 1);
+            assert_eq!(*last_page.get(0), managed_address!(&carol));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut addresses = MultiValueEncoded::new();
+            addresses.push(managed_address!(bob));
+
+            sc.remove_whitelist_per_drop_many(addresses);
+
+            assert_eq!(sc.get_whitelist_count_per_drop(1), 2);
+            assert!(!sc.get_is_whitelisted_per_drop(1, managed_address!(bob)));
+            assert!(sc.get_is_whitelisted_per_drop(1, managed_address!(&carol)));
+        })
+        .assert_ok();
+
+    // -----------------------
+    // the list is copied to another drop in pages
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.copy_whitelist(1, 1, 0, 10);
+        })
+        .assert_user_error("Can't copy a whitelist to the same drop!");
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.copy_whitelist(1, 2, 0, 1);
+
+            assert_eq!(sc.get_whitelist_count_per_drop(2), 1);
+
+            sc.copy_whitelist(1, 2, 1, 1);
+
+            assert_eq!(sc.get_whitelist_count_per_drop(2), 2);
+            assert!(sc.get_is_whitelisted_per_drop(2, managed_address!(alice)));
+            assert!(sc.get_is_whitelisted_per_drop(2, managed_address!(&carol)));
+        })
+        .assert_ok();
+
+    // -----------------------
+    // the legacy whitelist takes lists too
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut addresses = MultiValueEncoded::new();
+            addresses.push(managed_address!(alice));
+            addresses.push(managed_address!(bob));
+
+            sc.approve_whitelist_many(addresses);
+
+            let mut addresses = MultiValueEncoded::new();
+            addresses.push(managed_address!(alice));
+
+            sc.remove_whitelist(addresses);
+
+            assert!(!sc.is_whitelisted(&managed_address!(alice)).get());
+            assert!(sc.is_whitelisted(&managed_address!(bob)).get());
+        })
+        .assert_ok();
+}
+
+#[test]
+fn whitelist_tier_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+
+    b_wrapper.set_esdt_balance(alice, USDC_TOKEN_ID, &rust_biguint!(1_000u64));
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let drop_id = sc.opened_drop().get();
+            let mut addresses = MultiValueEncoded::new();
+            addresses.push(managed_address!(alice));
+
+            sc.set_whitelist_tier(drop_id, 1, managed_biguint!(60u64), 2, OptionalValue::None);
+            sc.add_to_whitelist_tier(drop_id, 1, addresses);
+            sc.set_presale_payment_price(managed_token_id!(USDC_TOKEN_ID), managed_biguint!(5u64));
+            sc.resume_presale();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // the tier price is in EGLD, ESDT payments can't skip it
+    // -----------------------
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, USDC_TOKEN_ID, 0, &rust_biguint!(5u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(USDC_TOKEN_ID),
+                0,
+                managed_biguint!(5u64),
+                OptionalValue::Some(1),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Payment token not accepted!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(120u64), |sc| {
+            sc.mint_presale(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(120u64),
+                OptionalValue::Some(2),
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(120u64));
+}
+
+const GATE_TOKEN_ID: &[u8] = b"GATE-123456";
+
+#[test]
+fn gated_presale_egld_deposit_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+
+    b_wrapper.set_nft_balance(
+        alice,
+        GATE_TOKEN_ID,
+        1,
+        &rust_biguint!(1u64),
+        &rust_biguint!(1u64),
+    );
+    b_wrapper.set_nft_balance(
+        alice,
+        GATE_TOKEN_ID,
+        2,
+        &rust_biguint!(1u64),
+        &rust_biguint!(1u64),
+    );
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_gated_collection(managed_token_id!(GATE_TOKEN_ID), 2);
+            sc.resume_presale();
+        })
+        .assert_ok();
+
+    let gate_transfer = |nonce: u64| {
+        [TxInputESDT {
+            token_identifier: GATE_TOKEN_ID.to_vec(),
+            nonce,
+            value: rust_biguint!(1u64),
+        }]
+    };
+
+    // -----------------------
+    // the deposit pays for the 2 mints the NFT grants
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(100u64), |sc| {
+            sc.deposit_presale_payment(managed_biguint!(100u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_multi_transfer(alice, &sc, &gate_transfer(1), |sc| {
+            sc.mint_presale_with_nfts();
+
+            assert_eq!(sc.minted_indexes_total().get(), 2);
+            assert_eq!(
+                sc.presale_deposit(&managed_address!(alice)).get(),
+                managed_biguint!(0u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_nft_balance(
+        alice,
+        GATE_TOKEN_ID,
+        1,
+        &rust_biguint!(1u64),
+        Some(&rust_biguint!(1u64)),
+    );
+    b_wrapper.check_egld_balance(owner, &rust_biguint!(100u64));
+
+    b_wrapper
+        .execute_esdt_multi_transfer(alice, &sc, &gate_transfer(1), |sc| {
+            sc.mint_presale_with_nfts();
+        })
+        .assert_user_error("Token already used in this drop!");
+
+    // -----------------------
+    // a wrong deposit is rejected and can be withdrawn
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(60u64), |sc| {
+            sc.deposit_presale_payment(managed_biguint!(60u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_multi_transfer(alice, &sc, &gate_transfer(2), |sc| {
+            sc.mint_presale_with_nfts();
+        })
+        .assert_user_error("Invalid amount as payment");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.withdraw_presale_payment();
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(9_900u64));
+}
+
+// the staking contract view the minter queries for stakers
+mod staking_mock {
+    elrond_wasm::imports!();
+
+    #[elrond_wasm::contract]
+    pub trait StakingMock {
+        #[init]
+        fn init(&self) {}
+
+        #[view(getStakedNfts)]
+        #[storage_mapper("stakedNfts")]
+        fn staked_nfts(&self, address: &ManagedAddress) -> UnorderedSetMapper<u64>;
+    }
+}
+
+#[test]
+fn staker_mint_test() {
+    use staking_mock::StakingMock;
+
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+
+    // -----------------------
+    // alice stakes NFT 1
+    // -----------------------
+
+    let staking_wrapper = b_wrapper.create_sc_account(
+        &rust_biguint!(0u64),
+        Some(owner),
+        staking_mock::contract_obj,
+        "staking-mock.wasm",
+    );
+
+    b_wrapper
+        .execute_tx(owner, &staking_wrapper, &rust_biguint!(0u64), |sc| {
+            sc.staked_nfts(&managed_address!(alice)).insert(1);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_staking_reward_share(managed_address!(staking_wrapper.address_ref()), 0);
+            sc.set_staker_perks(3, 0);
+            sc.resume_presale();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // the free mints of one NFT can be used over several calls
+    // -----------------------
+
+    for tokens in [2u32, 1] {
+        b_wrapper
+            .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+                sc.mint_as_staker(
+                    managed_token_id!(b"EGLD"),
+                    0,
+                    managed_biguint!(0u64),
+                    tokens,
+                    MultiValueEncoded::new(),
+                );
+            })
+            .assert_ok();
+    }
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.mint_as_staker(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(0u64),
+                1,
+                MultiValueEncoded::new(),
+            );
+        })
+        .assert_user_error("Not enough unused staked NFTs!");
+
+    b_wrapper
+        .execute_query(&sc, |sc| {
+            let drop_id = sc.opened_drop().get();
+
+            assert_eq!(sc.staked_nft_mints(drop_id, 1).get(), 3);
+            assert_eq!(sc.minted_indexes_total().get(), 3);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn random_mint_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+    let alice = &setup.alice;
+    let bob = &setup.bob;
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_random_mint(true);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // giveaways and regular mints draw from the same indexes
+    // -----------------------
+
+    for i in 0..10u8 {
+        b_wrapper.set_block_random_seed(Box::new([i * 7 + 1; 48]));
+
+        if i % 3 == 0 {
+            b_wrapper
+                .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+                    sc.giveaway(managed_address!(bob), 1);
+                })
+                .assert_ok();
+        } else {
+            b_wrapper
+                .execute_tx(alice, &sc, &rust_biguint!(100u64), |sc| {
+                    sc.mint(
+                        managed_token_id!(b"EGLD"),
+                        0,
+                        managed_biguint!(100u64),
+                        OptionalValue::Some(1),
+                    );
+                })
+                .assert_ok();
+        }
+    }
+
+    b_wrapper
+        .execute_query(&sc, |sc| {
+            assert_eq!(sc.total_tokens_left(), 0);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(100u64), |sc| {
+            sc.mint(
+                managed_token_id!(b"EGLD"),
+                0,
+                managed_biguint!(100u64),
+                OptionalValue::Some(1),
+            );
+        })
+        .assert_user_error("All tokens have been minted already (totally or per drop)!");
+
+    // -----------------------
+    // every index is minted exactly once
+    // -----------------------
+
+    let mut minted_indexes = Vec::new();
+
+    for nonce in 1..=10u64 {
+        let holder = if b_wrapper.get_esdt_balance(bob, NFT_TOKEN_ID, nonce) > rust_biguint!(0u64) {
+            bob
+        } else {
+            alice
+        };
+
+        let attributes: Vec<u8> = b_wrapper
+            .get_nft_attributes(holder, NFT_TOKEN_ID, nonce)
+            .unwrap();
+        let attributes = String::from_utf8(attributes).unwrap();
+
+        let file = attributes.rsplit('/').next().unwrap();
+        let index: u32 = file.trim_end_matches(".json").parse().unwrap();
+
+        minted_indexes.push(index);
+    }
+
+    minted_indexes.sort_unstable();
+    assert_eq!(minted_indexes, (1..=10).collect::<Vec<u32>>());
+}
+
+#[test]
+fn timelock_royalties_test() {
+    let mut setup = setup_minter(deainostri_nft_minter::contract_obj, 10);
+    let b_wrapper = &mut setup.blockchain_wrapper;
+    let sc = &mut setup.sc_wrapper;
+
+    let owner = &setup.owner_address;
+
+    b_wrapper.set_block_timestamp(1_000);
+
+    // -----------------------
+    // royalties are checked when queued, not only when executed
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_royalties(managed_biguint!(10_001u64));
+        })
+        .assert_user_error("Royalties cannot exceed 100%!");
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_royalties(managed_biguint!(1_000u64));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+        })
+        .assert_user_error("Action is still timelocked!");
+
+    b_wrapper.set_block_timestamp(1_000 + 86_400);
+
+    b_wrapper
+        .execute_tx(owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+
+            assert_eq!(sc.royalties().get(), managed_biguint!(1_000u64));
+        })
+        .assert_ok();
+}
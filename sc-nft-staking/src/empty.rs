@@ -10,15 +10,41 @@ elrond_wasm::imports!();
 // pub mod nft_info;
 // use nft_info::*;
 
+pub mod streak_tier;
+use streak_tier::*;
+
+pub mod timelock_action;
+use timelock_action::*;
+
+use deainostri_roles::Role;
+
 const DAY_IN_SECONDS: u64 = 86400;
 const PERCENTAGE_PREC: u64 = 1000000;
+const PENALTY_PREC: u64 = 10000;
+const MULTIPLIER_PREC: u64 = 10000;
 
 #[elrond_wasm::contract]
-pub trait DeainostriNFTStaking {
+pub trait DeainostriNFTStaking:
+    deainostri_roles::RolesModule
+    + deainostri_roles::multisig::MultisigModule
+    + deainostri_roles::timelock::TimelockModule
+{
     //
 
     #[init]
-    fn init(&self) {}
+    fn init(&self) {
+        self.init_timelock();
+
+        // rewards credited before the reserve existed were never booked,
+        // withdrawals wait for syncReservedFunds to go through them
+        if !self.reserves_synced().get() {
+            if self.staked_adresses().is_empty() {
+                self.reserves_synced().set(true);
+            } else {
+                self.reserves_pending().set(true);
+            }
+        }
+    }
 
     // -----------------------
     // action methods
@@ -27,6 +53,27 @@ pub trait DeainostriNFTStaking {
     #[payable("*")]
     #[endpoint(stake)]
     fn stake(&self) {
+        self.stake_nft(&self.blockchain().get_caller());
+    }
+
+    #[payable("*")]
+    #[endpoint(stakeFor)]
+    fn stake_for(&self, beneficiary: ManagedAddress) {
+        //
+        require!(!beneficiary.is_zero(), "Invalid beneficiary!");
+
+        require!(
+            self.is_stake_for_restricted().is_empty()
+                || self
+                    .stake_for_callers()
+                    .contains(&self.blockchain().get_caller()),
+            "Caller is not allowed to stake for others!"
+        );
+
+        self.stake_nft(&beneficiary);
+    }
+
+    fn stake_nft(&self, beneficiary: &ManagedAddress) {
         //
         let received_token = self.call_value().single_esdt();
         let nft_amount = received_token.amount;
@@ -45,46 +92,77 @@ pub trait DeainostriNFTStaking {
             "Invalid NFT amount received!"
         );
 
-        // marked nft as owned by caller
-        self.nft_owner(&received_token.token_nonce)
-            .set(self.blockchain().get_caller());
+        // receipt positions are booked under the receipt, not under the beneficiary
+        let owner = if self.receipt_token_id().is_empty() {
+            beneficiary.clone()
+        } else {
+            self.mint_receipt(received_token.token_nonce, beneficiary)
+        };
+
+        // marked nft as owned by beneficiary
+        self.nft_owner(&received_token.token_nonce).set(&owner);
 
         // mark timestamp when nft was staked
         self.nft_staked_at(&received_token.token_nonce)
             .set(self.blockchain().get_block_timestamp());
 
+        // mark timestamp when the stake started, kept for early unstake checks
+        self.nft_stake_start(&received_token.token_nonce)
+            .set(self.blockchain().get_block_timestamp());
+
         // insert nonce into staked nfts
-        self.nfts_staked_by_address(&self.blockchain().get_caller())
+        self.nfts_staked_by_address(&owner)
             .insert(received_token.token_nonce);
 
         // insert address in to staked addresses
-        self.staked_adresses()
-            .insert(self.blockchain().get_caller());
+        self.staked_adresses().insert(owner);
     }
 
+    #[payable("*")]
     #[endpoint(unstake)]
     fn unstake(&self, nonce: u64) {
         //
         require!(self.is_locked().is_empty(), "SC is locked!");
 
-        //
         require!(
-            self.is_nft_staked_by_caller(nonce),
-            "NFT is not staked by you!"
+            self.call_value().egld_value() == 0,
+            "Only receipts can be sent!"
         );
 
-        // compute new points
+        let caller = self.blockchain().get_caller();
+        let receipts = self.call_value().all_esdt_transfers();
+
+        // positions with a receipt are unstaked by whoever sends the receipt back
+        let owner = if receipts.is_empty() {
+            require!(
+                self.is_nft_staked_by_caller(nonce),
+                "NFT is not staked by you!"
+            );
+
+            caller.clone()
+        } else {
+            self.burn_receipt(&nonce, &receipts)
+        };
+
+        // compute new points, minus the early unstake penalty
         self.add_new_points_by_nft(&nonce);
 
         // remove nft from owner map
         self.nft_owner(&nonce).clear();
 
-        // remove nft from timestamp map
+        // remove nft from timestamp maps
         self.nft_staked_at(&nonce).clear();
+        self.nft_stake_start(&nonce).clear();
+        self.nft_stake_start_backfilled(&nonce).clear();
+        self.nft_accrued_points(&nonce).clear();
 
         // remove nft from staked nfts map
-        self.nfts_staked_by_address(&self.blockchain().get_caller())
-            .swap_remove(&nonce);
+        self.nfts_staked_by_address(&owner).swap_remove(&nonce);
+
+        // receipt position is closed, its points and rewards go to the holder
+        if owner != caller {
+            self.close_receipt_position(&owner, &caller);
+        }
 
         // send nft to owner
         self.send().direct(
@@ -96,9 +174,48 @@ pub trait DeainostriNFTStaking {
         );
     }
 
-    #[only_owner]
+    #[endpoint(transferStake)]
+    fn transfer_stake(&self, nonces: ManagedVec<u64>, new_owner: ManagedAddress) {
+        //
+        require!(self.is_locked().is_empty(), "SC is locked!");
+
+        let caller = self.blockchain().get_caller();
+
+        require!(!new_owner.is_zero(), "Invalid new owner!");
+        require!(new_owner != caller, "Cannot transfer stake to yourself!");
+
+        for nonce in nonces.iter() {
+            require!(
+                self.is_nft_staked_by_caller(nonce),
+                "NFT is not staked by you!"
+            );
+
+            // points earned so far stay with the caller
+            self.settle_points_by_nft(&nonce);
+
+            // the new owner starts a fresh stake, streak and early unstake checks included
+            self.nft_staked_at(&nonce)
+                .set(self.blockchain().get_block_timestamp());
+            self.nft_stake_start(&nonce)
+                .set(self.blockchain().get_block_timestamp());
+            self.nft_stake_start_backfilled(&nonce).clear();
+            self.nft_accrued_points(&nonce).clear();
+
+            // move nft to the new owner, it never leaves the sc
+            self.nft_owner(&nonce).set(&new_owner);
+            self.nfts_staked_by_address(&caller).swap_remove(&nonce);
+            self.nfts_staked_by_address(&new_owner).insert(nonce);
+        }
+
+        self.staked_adresses().insert(new_owner.clone());
+
+        self.transfer_stake_event(&caller, &new_owner, &nonces);
+    }
+
     #[endpoint(computePoints)]
     fn compute_all_points(&self) {
+        self.require_role(Role::Operator);
+
         let current_index = if self.compute_index().is_empty() {
             0
         } else {
@@ -118,9 +235,12 @@ pub trait DeainostriNFTStaking {
                 // compute new points
                 let points_to_add = self.get_new_points_by_nft(&nft_nonce);
 
-                // reset nft staked at date
-                self.nft_staked_at(&nft_nonce)
-                    .set(self.blockchain().get_block_timestamp());
+                // move nft staked at date forward, keeping the partial tick
+                self.checkpoint_nft(&nft_nonce);
+
+                // keep what the nft earned since its stake started for the penalty
+                self.nft_accrued_points(&nft_nonce)
+                    .update(|sum| *sum += points_to_add);
 
                 // add new points
                 points += points_to_add;
@@ -143,9 +263,13 @@ pub trait DeainostriNFTStaking {
         // self.total_points().set(total_points);
     }
 
-    #[only_owner]
     #[endpoint(computeTotalPoints)]
     fn compute_total_points(&self) {
+        self.require_role(Role::Operator);
+
+        // hand forfeited points to the remaining stakers first
+        self.redistribute_forfeited_points();
+
         let mut total_points: u64 = 0;
 
         // iterate over all staked addresses
@@ -159,15 +283,20 @@ pub trait DeainostriNFTStaking {
         self.total_points().set(total_points);
     }
 
-    #[only_owner]
     #[endpoint(resetComputeIndex)]
     fn reset_compute_index(&self) {
+        self.require_role(Role::Operator);
+
+        // a new compute round starts, closed receipt positions can go
+        self.prune_closed_positions();
+
         self.compute_index().set(0);
     }
 
-    #[only_owner]
     #[endpoint(computePercentages)]
     fn compute_percentages(&self) {
+        self.require_role(Role::Operator);
+
         let total_points = self.total_points().get();
         let current_index = if self.compute_index().is_empty() {
             0
@@ -199,11 +328,16 @@ pub trait DeainostriNFTStaking {
         // self.total_points().set(0);
     }
 
-    #[only_owner]
     #[payable("*")]
     #[endpoint(addStakeReward)]
     fn add_stake_reward(&self) {
-        let reward_amount = self.call_value().egld_value();
+        self.require_role(Role::Treasurer);
+
+        let reward_amount = self.call_value().egld_value()
+            + self.take_rolled_over_rewards()
+            + self.take_reward_deposits();
+
+        self.open_reward_round();
 
         // iterate over all staked addresses
         for address in self.staked_adresses().iter() {
@@ -212,14 +346,14 @@ pub trait DeainostriNFTStaking {
 
             // set new percentage
             self.points_by_address(&address).set(0);
-            self.claimable_by_address(&address)
-                .set(claimable + self.claimable_by_address(&address).get());
+            self.credit_reward(&address, claimable);
         }
     }
 
-    #[only_owner]
     #[endpoint(resolveStakeReward)]
     fn resolve_stake_reward(&self, reward_amount: BigUint) {
+        self.require_role(Role::Operator);
+
         let current_index = if self.compute_index().is_empty() {
             0
         } else {
@@ -228,6 +362,14 @@ pub trait DeainostriNFTStaking {
         let max_index = self.staked_adresses().len() as u64;
         let mut new_index = current_index;
 
+        // the first page opens a new round, rolled over rewards and deposits join it
+        if self.resolving_reward_amount().is_empty() {
+            self.open_reward_round();
+            self.resolving_reward_amount()
+                .set(reward_amount + self.take_rolled_over_rewards() + self.take_reward_deposits());
+        }
+        let reward_amount = self.resolving_reward_amount().get();
+
         // iterate over all staked addresses
         // for address in self.staked_adresses().iter() {
         for index in current_index..max_index {
@@ -239,8 +381,7 @@ pub trait DeainostriNFTStaking {
 
             // set new percentage
             self.points_by_address(&address).set(0);
-            self.claimable_by_address(&address)
-                .set(claimable + self.claimable_by_address(&address).get());
+            self.credit_reward(&address, claimable);
 
             // if not enough gas, intrerrupt
             if self.blockchain().get_gas_left() < 10000000 {
@@ -249,56 +390,732 @@ pub trait DeainostriNFTStaking {
         }
 
         self.compute_index().set(new_index);
+
+        // round fully resolved
+        if new_index + 1 >= max_index {
+            self.resolving_reward_amount().clear();
+        }
+    }
+
+    // -----------------------
+    // reward round methods
+    // -----------------------
+
+    fn open_reward_round(&self) {
+        let round = self.current_reward_round().update(|round| {
+            *round += 1;
+            *round
+        });
+
+        // rounds opened without a claim period never expire
+        if !self.claim_period().is_empty() {
+            self.reward_round_deadline(round)
+                .set(self.blockchain().get_block_timestamp() + self.claim_period().get());
+        }
+    }
+
+    // deposits are paid out with the round currently being accumulated
+    #[payable("EGLD")]
+    #[endpoint(depositReward)]
+    fn deposit_reward(&self) {
+        let amount = self.call_value().egld_value();
+        require!(amount > 0, "Invalid amount!");
+
+        self.reward_deposits()
+            .update(|deposits| *deposits += &amount);
+        self.reserve_funds(None, &amount);
+
+        self.reward_deposit_event(&self.blockchain().get_caller(), &amount);
+    }
+
+    fn take_reward_deposits(&self) -> BigUint {
+        let deposits = self.reward_deposits().get();
+        self.reward_deposits().clear();
+        self.release_funds(None, &deposits);
+
+        deposits
+    }
+
+    fn take_rolled_over_rewards(&self) -> BigUint {
+        let rolled_over = self.rolled_over_rewards().get();
+        self.rolled_over_rewards().clear();
+        self.release_funds(None, &rolled_over);
+
+        rolled_over
+    }
+
+    fn credit_reward(&self, address: &ManagedAddress, amount: BigUint) {
+        if amount == 0 {
+            return;
+        }
+
+        let round = self.current_reward_round().get();
+
+        if !self.reward_round_deadline(round).is_empty() {
+            let unclaimed = self
+                .unclaimed_by_round(address)
+                .get(&round)
+                .unwrap_or_default();
+
+            self.unclaimed_by_round(address)
+                .insert(round, unclaimed + &amount);
+        }
+
+        self.reserve_funds(None, &amount);
+        self.claimable_by_address(address)
+            .update(|claimable| *claimable += amount);
+    }
+
+    #[endpoint(expireRewards)]
+    fn expire_rewards(&self) {
+        self.require_role(Role::Operator);
+
+        // rolling over moves claimables without touching the reserve
+        self.require_reserves_synced();
+
+        let max_index = self.staked_adresses().len() as u64;
+
+        // start a new pass once the previous one went through all addresses
+        let current_index = if self.expiry_index().get() >= max_index {
+            0
+        } else {
+            self.expiry_index().get()
+        };
+        let mut new_index = current_index;
+        let mut expired = BigUint::zero();
+
+        // iterate over the staked addresses left in this pass
+        for address in self.staked_adresses().iter().skip(current_index as usize) {
+            new_index += 1;
+
+            expired += self.expire_rewards_by_address(&address);
+
+            // if not enough gas, intrerrupt
+            if self.blockchain().get_gas_left() < 10000000 {
+                break;
+            }
+        }
+
+        self.expiry_index().set(new_index);
+
+        if expired == 0 {
+            return;
+        }
+
+        self.total_expired_rewards()
+            .update(|total| *total += &expired);
+
+        // send to treasury, or keep for the next round
+        if self.expired_rewards_treasury().is_empty() {
+            self.rolled_over_rewards()
+                .update(|rolled_over| *rolled_over += expired);
+        } else {
+            self.release_funds(None, &expired);
+            self.send()
+                .direct_egld(&self.expired_rewards_treasury().get(), &expired, &[]);
+        }
+    }
+
+    fn expire_rewards_by_address(&self, address: &ManagedAddress) -> BigUint {
+        let now = self.blockchain().get_block_timestamp();
+        let mut expired = BigUint::zero();
+        let mut expired_rounds: ManagedVec<u64> = ManagedVec::new();
+
+        for (round, amount) in self.unclaimed_by_round(address).iter() {
+            if self.reward_round_deadline(round).get() < now {
+                expired += amount;
+                expired_rounds.push(round);
+            }
+        }
+
+        for round in expired_rounds.iter() {
+            self.unclaimed_by_round(address).remove(&round);
+        }
+
+        // never take more than what is still claimable
+        let claimable = self.claimable_by_address(address).get();
+        if expired > claimable {
+            expired = claimable.clone();
+        }
+
+        self.claimable_by_address(address).set(claimable - &expired);
+
+        expired
+    }
+
+    #[endpoint(syncReservedFunds)]
+    fn sync_reserved_funds(&self) {
+        self.require_role(Role::Operator);
+
+        require!(
+            self.reserves_pending().get(),
+            "Reserved funds are already synced!"
+        );
+
+        let max_index = self.staked_adresses().len() as u64;
+        let mut new_index = self.reserve_sync_index().get();
+        let mut owed = self.reserve_sync_total().get();
+
+        // claimables can't change meanwhile, reserving and releasing funds
+        // waits for the sync
+        for address in self.staked_adresses().iter().skip(new_index as usize) {
+            new_index += 1;
+
+            owed += self.claimable_by_address(&address).get();
+
+            // if not enough gas, intrerrupt
+            if self.blockchain().get_gas_left() < 10000000 {
+                break;
+            }
+        }
+
+        if new_index < max_index {
+            self.reserve_sync_index().set(new_index);
+            self.reserve_sync_total().set(&owed);
+            return;
+        }
+
+        // deposits and rolled over rewards are owed to the next round
+        owed += self.reward_deposits().get() + self.rolled_over_rewards().get();
+
+        self.reserved_funds(&None).set(&owed);
+        self.reserve_sync_index().clear();
+        self.reserve_sync_total().clear();
+        self.reserves_pending().clear();
+        self.reserves_synced().set(true);
+    }
+
+    #[view(getUpcomingExpiries)]
+    fn get_upcoming_expiries(
+        &self,
+        address: ManagedAddress,
+    ) -> MultiValueEncoded<MultiValue2<u64, BigUint>> {
+        let mut expiries = MultiValueEncoded::new();
+
+        for (round, amount) in self.unclaimed_by_round(&address).iter() {
+            expiries.push((self.reward_round_deadline(round).get(), amount).into());
+        }
+
+        expiries
     }
 
     #[endpoint(claim)]
-    fn claim(&self) {
+    fn claim(&self, staker: OptionalValue<ManagedAddress>) {
         //
         require!(self.is_locked().is_empty(), "SC is locked!");
 
+        let caller = self.blockchain().get_caller();
+        let staker = staker.into_option().unwrap_or_else(|| caller.clone());
+
+        // only the staker or their delegate can trigger a claim
+        require!(
+            staker == caller
+                || (!self.claim_delegate(&staker).is_empty()
+                    && self.claim_delegate(&staker).get() == caller),
+            "Caller is not allowed to claim for this address!"
+        );
+
+        self.pay_out_claimable(&staker);
+    }
+
+    fn pay_out_claimable(&self, staker: &ManagedAddress) {
         //
-        let claimable = self
-            .claimable_by_address(&self.blockchain().get_caller())
-            .get();
+        let claimable = self.claimable_by_address(staker).get();
 
-        let claimed = &self.claimed_by_address(&self.blockchain().get_caller())
-            .get();
+        let claimed = &self.claimed_by_address(staker).get();
 
-        self.claimed_by_address(&self.blockchain().get_caller())
-            .set(claimed + &claimable);
+        self.claimed_by_address(staker).set(claimed + &claimable);
 
-        self.claimable_by_address(&self.blockchain().get_caller())
-            .set(&BigUint::zero());
+        self.claimable_by_address(staker).set(&BigUint::zero());
+        self.unclaimed_by_round(staker).clear();
+        self.release_funds(None, &claimable);
 
         self.send()
-            .direct_egld(&self.blockchain().get_caller(), &claimable, &[]);
+            .direct_egld(&self.get_reward_recipient(staker), &claimable, &[]);
+    }
+
+    #[endpoint(airdropRewards)]
+    fn airdrop_rewards(&self) {
+        self.require_role(Role::Operator);
+
+        let max_index = self.staked_adresses().len() as u64;
+
+        // start a new round once the previous one went through all addresses
+        let current_index = if self.airdrop_index().get() >= max_index {
+            0
+        } else {
+            self.airdrop_index().get()
+        };
+        let mut new_index = current_index;
+
+        // iterate over the staked addresses left in this round
+        for address in self.staked_adresses().iter().skip(current_index as usize) {
+            new_index += 1;
+
+            let recipient = self.get_reward_recipient(&address);
+
+            // receipt positions are claimed by the receipt holder
+            if self.claimable_by_address(&address).get() > 0
+                && !self.is_receipt_position(&address).get()
+            {
+                if !self.blockchain().is_smart_contract(&recipient) {
+                    self.pay_out_claimable(&address);
+                } else if recipient != address {
+                    // contracts might not be payable, their rewards wait for
+                    // them to claim by themselves
+                    self.move_claimable(&address, &recipient);
+                    self.staked_adresses().insert(recipient);
+                }
+            }
+
+            // if not enough gas, intrerrupt
+            if self.blockchain().get_gas_left() < 10000000 {
+                break;
+            }
+        }
+
+        self.airdrop_index().set(new_index);
+    }
+
+    #[view(getAirdropProgress)]
+    fn get_airdrop_progress(&self) -> MultiValue2<u64, u64> {
+        (
+            self.airdrop_index().get(),
+            self.staked_adresses().len() as u64,
+        )
+            .into()
+    }
+
+    #[endpoint(setRewardRecipient)]
+    fn set_reward_recipient(&self, recipient: OptionalValue<ManagedAddress>) {
+        let caller = self.blockchain().get_caller();
+
+        match recipient.into_option() {
+            Some(recipient) => {
+                require!(!recipient.is_zero(), "Invalid reward recipient!");
+                require!(
+                    recipient != self.blockchain().get_sc_address(),
+                    "Invalid reward recipient!"
+                );
+
+                self.reward_recipient(&caller).set(recipient);
+            }
+            None => self.reward_recipient(&caller).clear(),
+        }
+    }
+
+    #[endpoint(setClaimDelegate)]
+    fn set_claim_delegate(&self, delegate: OptionalValue<ManagedAddress>) {
+        let caller = self.blockchain().get_caller();
+
+        match delegate.into_option() {
+            Some(delegate) => {
+                require!(!delegate.is_zero(), "Invalid claim delegate!");
+                require!(delegate != caller, "Cannot delegate claims to yourself!");
+
+                self.claim_delegate(&caller).set(delegate);
+            }
+            None => self.claim_delegate(&caller).clear(),
+        }
+    }
+
+    fn get_reward_recipient(&self, address: &ManagedAddress) -> ManagedAddress {
+        if self.reward_recipient(address).is_empty() {
+            address.clone()
+        } else {
+            self.reward_recipient(address).get()
+        }
+    }
+
+    #[payable("*")]
+    #[endpoint(claimWithReceipt)]
+    fn claim_with_receipt(&self) {
+        //
+        require!(self.is_locked().is_empty(), "SC is locked!");
+
+        require!(
+            self.call_value().egld_value() == 0,
+            "Only receipts can be sent!"
+        );
+
+        let caller = self.blockchain().get_caller();
+        let receipts = self.call_value().all_esdt_transfers();
+        let mut claimable = BigUint::zero();
+
+        require!(!receipts.is_empty(), "No receipt received!");
+
+        for receipt in receipts.iter() {
+            self.require_valid_receipt(&receipt);
+
+            let position = self.get_receipt_position_address(receipt.token_nonce);
+            let position_claimable = self.claimable_by_address(&position).get();
+
+            self.claimed_by_address(&position)
+                .update(|claimed| *claimed += &position_claimable);
+            self.claimable_by_address(&position).clear();
+            self.unclaimed_by_round(&position).clear();
+
+            claimable += position_claimable;
+
+            // hand the receipt back
+            self.send().direct_esdt(
+                &caller,
+                &receipt.token_identifier,
+                receipt.token_nonce,
+                &receipt.amount,
+                &[],
+            );
+        }
+
+        self.release_funds(None, &claimable);
+        self.send().direct_egld(&caller, &claimable, &[]);
+    }
+
+    // -----------------------
+    // receipt methods
+    // -----------------------
+
+    fn mint_receipt(&self, nonce: u64, receiver: &ManagedAddress) -> ManagedAddress {
+        let receipt_token_id = self.receipt_token_id().get();
+        let amount = BigUint::from(REQUIRED_NFT_AMOUNT);
+
+        let receipt_nonce = self.send().esdt_nft_create(
+            &receipt_token_id,
+            &amount,
+            &ManagedBuffer::new(),
+            &BigUint::zero(),
+            &ManagedBuffer::new(),
+            &nonce,
+            &ManagedVec::new(),
+        );
+
+        self.nft_by_receipt(receipt_nonce).set(nonce);
+        self.receipt_by_nft(&nonce).set(receipt_nonce);
+
+        self.send()
+            .direct_esdt(receiver, &receipt_token_id, receipt_nonce, &amount, &[]);
+
+        let position = self.get_receipt_position_address(receipt_nonce);
+        self.is_receipt_position(&position).set(true);
+
+        position
+    }
+
+    fn burn_receipt(&self, nonce: &u64, receipts: &ManagedVec<EsdtTokenPayment>) -> ManagedAddress {
+        require!(receipts.len() == 1, "Only one receipt can be sent!");
+
+        let receipt = receipts.get(0);
+        self.require_valid_receipt(&receipt);
+
+        require!(
+            self.nft_by_receipt(receipt.token_nonce).get() == *nonce,
+            "Receipt does not match the NFT!"
+        );
+
+        self.send().esdt_local_burn(
+            &receipt.token_identifier,
+            receipt.token_nonce,
+            &receipt.amount,
+        );
+
+        self.nft_by_receipt(receipt.token_nonce).clear();
+        self.receipt_by_nft(nonce).clear();
+
+        self.get_receipt_position_address(receipt.token_nonce)
+    }
+
+    fn require_valid_receipt(&self, receipt: &EsdtTokenPayment) {
+        require!(
+            !self.receipt_token_id().is_empty()
+                && receipt.token_identifier == self.receipt_token_id().get(),
+            "Invalid receipt received!"
+        );
+
+        require!(
+            receipt.amount == REQUIRED_NFT_AMOUNT,
+            "Invalid receipt amount received!"
+        );
+
+        require!(
+            !self.nft_by_receipt(receipt.token_nonce).is_empty(),
+            "Receipt is not active!"
+        );
+    }
+
+    fn close_receipt_position(&self, position: &ManagedAddress, holder: &ManagedAddress) {
+        let points = self.points_by_address(position).get();
+
+        self.points_by_address(holder).update(|sum| *sum += points);
+        self.points_by_address(position).clear();
+
+        self.move_claimable(position, holder);
+
+        // removing it now would shift the addresses of a paginated round
+        self.closed_positions().insert(position.clone());
+        self.staked_adresses().insert(holder.clone());
+    }
+
+    fn move_claimable(&self, from: &ManagedAddress, to: &ManagedAddress) {
+        // claimables move between addresses, which a reserve sync can't follow
+        self.require_reserves_synced();
+
+        let claimable = self.claimable_by_address(from).get();
+
+        self.claimable_by_address(to)
+            .update(|sum| *sum += &claimable);
+
+        // unclaimed rewards keep their deadlines
+        for (round, amount) in self.unclaimed_by_round(from).iter() {
+            let unclaimed = self.unclaimed_by_round(to).get(&round).unwrap_or_default();
+
+            self.unclaimed_by_round(to)
+                .insert(round, unclaimed + amount);
+        }
+
+        self.claimable_by_address(from).clear();
+        self.unclaimed_by_round(from).clear();
+    }
+
+    fn prune_closed_positions(&self) {
+        let max_index = self.staked_adresses().len() as u64;
+        let airdrop_index = self.airdrop_index().get();
+        let expiry_index = self.expiry_index().get();
+
+        // wait for the airdrop and expiry rounds in progress to finish
+        if (airdrop_index > 0 && airdrop_index < max_index)
+            || (expiry_index > 0 && expiry_index < max_index)
+        {
+            return;
+        }
+
+        for position in self.closed_positions().iter() {
+            self.staked_adresses().remove(&position);
+            self.is_receipt_position(&position).clear();
+        }
+
+        self.closed_positions().clear();
+    }
+
+    #[view(getReceiptPositionAddress)]
+    fn get_receipt_position_address(&self, receipt_nonce: u64) -> ManagedAddress {
+        let mut seed = self.receipt_token_id().get().into_managed_buffer();
+        seed.append_bytes(&receipt_nonce.to_be_bytes());
+
+        ManagedAddress::from(self.crypto().sha256(&seed))
     }
 
     fn add_new_points_by_nft(&self, nonce: &u64) {
         //
         let points = self.get_new_points_by_nft(nonce);
         let address = self.nft_owner(nonce).get();
+        let available_points = points + self.points_by_address(&address).get();
+
+        // points already turned into rewards can't be taken back
+        let penalty = self
+            .get_early_unstake_penalty_by_nft(nonce, points)
+            .min(available_points);
+
+        if penalty > 0 {
+            self.forfeited_points().update(|sum| *sum += penalty);
+            self.total_forfeited_points().update(|sum| *sum += penalty);
+            self.forfeited_points_by_address(&address)
+                .update(|sum| *sum += penalty);
+        }
 
         // add new points to points map
         self.points_by_address(&address)
-            .set(points + self.points_by_address(&address).get());
+            .set(available_points - penalty);
     }
 
-    fn get_new_points_by_nft(&self, nonce: &u64) -> u64 {
+    fn settle_points_by_nft(&self, nonce: &u64) {
         //
-        if self.nft_staked_at(nonce).is_empty() || self.nft_owner(nonce).is_empty() {
+        let points = self.get_new_points_by_nft(nonce);
+        let address = self.nft_owner(nonce).get();
+
+        self.checkpoint_nft(nonce);
+
+        self.points_by_address(&address)
+            .update(|sum| *sum += points);
+    }
+
+    fn get_early_unstake_penalty_by_nft(&self, nonce: &u64, points: u64) -> u64 {
+        //
+        // backfilled stake starts are only checkpoints, the real one is unknown
+        if self.nft_stake_start(nonce).is_empty()
+            || self.nft_stake_start_backfilled(nonce).get()
+            || self.early_unstake_penalty().is_empty()
+        {
             return 0;
         }
 
-        let interval = if self.tick_interval().is_empty() {
+        let staked_for =
+            self.blockchain().get_block_timestamp() - self.nft_stake_start(nonce).get();
+
+        if staked_for >= self.min_stake_duration().get() {
+            return 0;
+        }
+
+        // the share applies to everything earned since the stake started
+        let accrued_points = self.nft_accrued_points(nonce).get() + points;
+
+        accrued_points * self.early_unstake_penalty().get() / PENALTY_PREC
+    }
+
+    fn redistribute_forfeited_points(&self) {
+        let forfeited_points = self.forfeited_points().get();
+
+        if forfeited_points == 0 {
+            return;
+        }
+
+        // only addresses that still have nfts staked get a share
+        let mut eligible_points: u64 = 0;
+        for address in self.staked_adresses().iter() {
+            if !self.nfts_staked_by_address(&address).is_empty() {
+                eligible_points += self.points_by_address(&address).get();
+            }
+        }
+
+        if eligible_points == 0 {
+            return;
+        }
+
+        let mut distributed_points: u64 = 0;
+        for address in self.staked_adresses().iter() {
+            if self.nfts_staked_by_address(&address).is_empty() {
+                continue;
+            }
+
+            let points = self.points_by_address(&address).get();
+            let share = forfeited_points * points / eligible_points;
+
+            self.points_by_address(&address).set(points + share);
+            distributed_points += share;
+        }
+
+        // rounding leftovers stay in the pool for the next round
+        self.forfeited_points()
+            .set(forfeited_points - distributed_points);
+    }
+
+    #[view(getEarlyUnstakePenaltyByNft)]
+    fn get_early_unstake_penalty_view(&self, nonce: u64) -> u64 {
+        let points = self.get_new_points_by_nft(&nonce);
+
+        self.get_early_unstake_penalty_by_nft(&nonce, points)
+    }
+
+    fn get_interval(&self) -> u64 {
+        if self.tick_interval().is_empty() {
             DAY_IN_SECONDS
         } else {
             self.tick_interval().get()
-        };
+        }
+    }
 
+    fn get_new_ticks_by_nft(&self, nonce: &u64) -> u64 {
         //
-        return (self.blockchain().get_block_timestamp() - self.nft_staked_at(nonce).get())
-            / interval;
+        if self.nft_staked_at(nonce).is_empty() || self.nft_owner(nonce).is_empty() {
+            return 0;
+        }
+
+        //
+        (self.blockchain().get_block_timestamp() - self.nft_staked_at(nonce).get())
+            / self.get_interval()
+    }
+
+    fn get_new_points_by_nft(&self, nonce: &u64) -> u64 {
+        //
+        let ticks = self.get_new_ticks_by_nft(nonce);
+
+        if ticks == 0 {
+            return 0;
+        }
+
+        let interval = self.get_interval();
+        let staked_at = self.nft_staked_at(nonce).get();
+
+        // streak already built up when the first new tick started
+        let streak_offset = staked_at.saturating_sub(self.get_stake_start(nonce));
+
+        // every tick is worth 1 point, tiers add on top of it once reached
+        let mut weighted_ticks = ticks * MULTIPLIER_PREC;
+        let mut previous_multiplier = MULTIPLIER_PREC;
+
+        for tier in self.streak_curve().get().iter() {
+            // first tick which ends with a streak of at least min_duration
+            let first_tick = if tier.min_duration <= streak_offset {
+                1
+            } else {
+                (tier.min_duration - streak_offset + interval - 1) / interval
+            };
+
+            if first_tick > ticks {
+                break;
+            }
+
+            let ticks_in_tier = ticks - first_tick + 1;
+            weighted_ticks += (tier.multiplier - previous_multiplier) * ticks_in_tier;
+            previous_multiplier = tier.multiplier;
+        }
+
+        weighted_ticks / MULTIPLIER_PREC
+    }
+
+    fn checkpoint_nft(&self, nonce: &u64) {
+        let ticks = self.get_new_ticks_by_nft(nonce);
+
+        // nfts staked before stake start tracking start their streak here
+        if self.nft_stake_start(nonce).is_empty() {
+            self.nft_stake_start(nonce)
+                .set(self.nft_staked_at(nonce).get());
+            self.nft_stake_start_backfilled(nonce).set(true);
+        }
+
+        self.nft_staked_at(nonce)
+            .update(|staked_at| *staked_at += ticks * self.get_interval());
+    }
+
+    fn get_stake_start(&self, nonce: &u64) -> u64 {
+        // nfts staked before stake start tracking fall back to the checkpoint
+        // until their first one
+        if self.nft_stake_start(nonce).is_empty() {
+            self.nft_staked_at(nonce).get()
+        } else {
+            self.nft_stake_start(nonce).get()
+        }
+    }
+
+    #[view(getStreakMultiplierByNft)]
+    fn get_streak_multiplier_by_nft(&self, nonce: u64) -> u64 {
+        if !self.is_nft_staked(nonce) {
+            return 0;
+        }
+
+        let streak = self
+            .blockchain()
+            .get_block_timestamp()
+            .saturating_sub(self.get_stake_start(&nonce));
+        let mut multiplier = MULTIPLIER_PREC;
+
+        for tier in self.streak_curve().get().iter() {
+            if tier.min_duration > streak {
+                break;
+            }
+
+            multiplier = tier.multiplier;
+        }
+
+        multiplier
+    }
+
+    #[view(getStakeStartByNft)]
+    fn get_stake_start_by_nft(&self, nonce: u64) -> u64 {
+        if !self.is_nft_staked(nonce) {
+            return 0;
+        }
+
+        self.get_stake_start(&nonce)
     }
 
     #[view(getNewPointsByAddress)]
@@ -332,34 +1149,241 @@ pub trait DeainostriNFTStaking {
     }
 
     #[only_owner]
+    #[payable("EGLD")]
+    #[endpoint(issueReceiptToken)]
+    fn issue_receipt_token(&self, token_name: ManagedBuffer, token_ticker: ManagedBuffer) {
+        require!(
+            self.receipt_token_id().is_empty(),
+            "Receipt token already issued!"
+        );
+
+        let issue_cost = self.call_value().egld_value();
+
+        self.send()
+            .esdt_system_sc_proxy()
+            .issue_non_fungible(
+                issue_cost,
+                &token_name,
+                &token_ticker,
+                NonFungibleTokenProperties {
+                    can_freeze: false,
+                    can_wipe: false,
+                    can_pause: false,
+                    can_change_owner: false,
+                    can_upgrade: true,
+                    can_add_special_roles: true,
+                },
+            )
+            .async_call()
+            .with_callback(self.callbacks().receipt_issue_callback())
+            .call_and_exit();
+    }
+
+    #[callback]
+    fn receipt_issue_callback(
+        &self,
+        #[call_result] result: ManagedAsyncCallResult<TokenIdentifier>,
+    ) {
+        match result {
+            ManagedAsyncCallResult::Ok(token_id) => {
+                self.receipt_token_id().set(&token_id);
+            }
+            ManagedAsyncCallResult::Err(_) => {
+                let owner = self.blockchain().get_owner_address();
+                let returned_tokens = self.call_value().egld_value();
+                if returned_tokens > 0 {
+                    self.send().direct_egld(&owner, &returned_tokens, &[]);
+                }
+            }
+        }
+    }
+
+    #[only_owner]
+    #[endpoint(setReceiptLocalRoles)]
+    fn set_receipt_local_roles(&self) {
+        require!(
+            !self.receipt_token_id().is_empty(),
+            "Receipt token not issued!"
+        );
+
+        self.send()
+            .esdt_system_sc_proxy()
+            .set_special_roles(
+                &self.blockchain().get_sc_address(),
+                &self.receipt_token_id().get(),
+                [EsdtLocalRole::NftCreate, EsdtLocalRole::NftBurn]
+                    .iter()
+                    .cloned(),
+            )
+            .async_call()
+            .call_and_exit();
+    }
+
+    #[only_owner]
+    #[endpoint(setReceiptTokenId)]
+    fn set_receipt_token_id(&self, token_identifier: TokenIdentifier) {
+        // open positions are keyed on the receipt token, it can't change
+        require!(
+            self.receipt_token_id().is_empty(),
+            "Receipt token already issued!"
+        );
+
+        self.receipt_token_id().set(token_identifier);
+    }
+
+    #[endpoint(setStakeForRestricted)]
+    fn set_stake_for_restricted(&self, restricted: bool) {
+        self.require_role(Role::Admin);
+
+        self.is_stake_for_restricted().set(restricted);
+    }
+
+    #[endpoint(addStakeForCaller)]
+    fn add_stake_for_caller(&self, address: ManagedAddress) {
+        self.require_role(Role::Admin);
+
+        self.stake_for_callers().insert(address);
+    }
+
+    #[endpoint(removeStakeForCaller)]
+    fn remove_stake_for_caller(&self, address: ManagedAddress) {
+        self.require_role(Role::Admin);
+
+        self.stake_for_callers().remove(&address);
+    }
+
     #[endpoint(setNftStartStakeDate)]
     fn set_nft_start_stake_date(&self, nonce: u64, start_stake_date: u64) {
-        //
-        self.nft_staked_at(&nonce).set(start_stake_date);
+        self.require_role(Role::Admin);
+
+        self.queue_action(&TimelockAction::<Self::Api>::SetNftStartStakeDate(
+            nonce,
+            start_stake_date,
+        ));
     }
 
-    #[only_owner]
     #[endpoint(lock)]
     fn lock(&self) {
+        self.require_role(Role::Admin);
+
         self.is_locked().set(true);
     }
 
-    #[only_owner]
     #[endpoint(unlock)]
     fn unlock(&self) {
+        self.require_role(Role::Admin);
+
         self.is_locked().clear();
     }
 
-    #[only_owner]
     #[endpoint(setTickInterval)]
     fn set_tick_interval(&self, tick_interval: u64) {
-        self.tick_interval().set(tick_interval);
+        self.require_role(Role::Admin);
+
+        self.queue_action(&TimelockAction::<Self::Api>::SetTickInterval(tick_interval));
+    }
+
+    #[endpoint(setEarlyUnstakePenalty)]
+    fn set_early_unstake_penalty(&self, min_stake_duration: u64, penalty: u64) {
+        self.require_role(Role::Admin);
+
+        require!(penalty <= PENALTY_PREC, "Penalty cannot exceed 100%!");
+
+        self.min_stake_duration().set(min_stake_duration);
+        self.early_unstake_penalty().set(penalty);
+    }
+
+    #[endpoint(setStreakCurve)]
+    fn set_streak_curve(&self, tiers: MultiValueEncoded<MultiValue2<u64, u64>>) {
+        self.require_role(Role::Admin);
+
+        let mut curve = ManagedVec::new();
+        let mut previous_tier = StreakTier {
+            min_duration: 0,
+            multiplier: MULTIPLIER_PREC,
+        };
+
+        for tier in tiers.into_iter() {
+            let (min_duration, multiplier) = tier.into_tuple();
+
+            require!(
+                min_duration > previous_tier.min_duration,
+                "Streak durations should be increasing!"
+            );
+            require!(
+                multiplier >= previous_tier.multiplier,
+                "Streak multipliers should not decrease!"
+            );
+
+            previous_tier = StreakTier {
+                min_duration,
+                multiplier,
+            };
+            curve.push(previous_tier.clone());
+        }
+
+        self.streak_curve().set(curve);
     }
 
-    #[only_owner]
     #[endpoint(fixStakeReward)]
     fn fix_stake_reward(&self, address: ManagedAddress, amount: BigUint) {
-        self.claimable_by_address(&address).set(&amount);
+        self.require_role(Role::Admin);
+
+        self.queue_action(&TimelockAction::FixStakeReward(address, amount));
+    }
+
+    #[endpoint(setRewardExpiry)]
+    fn set_reward_expiry(&self, claim_period: u64, treasury: OptionalValue<ManagedAddress>) {
+        self.require_role(Role::Admin);
+
+        self.claim_period().set(claim_period);
+
+        match treasury.into_option() {
+            Some(treasury) => {
+                require!(!treasury.is_zero(), "Invalid treasury!");
+
+                self.expired_rewards_treasury().set(treasury);
+            }
+            None => self.expired_rewards_treasury().clear(),
+        }
+    }
+
+    // -----------------------
+    // timelock methods
+    // -----------------------
+
+    // the queue lives in the timelock module, the actions are applied here
+    #[endpoint(executeAction)]
+    fn execute_action(&self, action_id: u64) {
+        self.require_role(Role::Admin);
+
+        if let Some(action) = self.take_ready_action(action_id) {
+            self.apply_action(action);
+        }
+    }
+
+    fn apply_action(&self, action: TimelockAction<Self::Api>) {
+        match action {
+            TimelockAction::FixStakeReward(address, amount) => {
+                self.release_funds(None, &self.claimable_by_address(&address).get());
+                self.reserve_funds(None, &amount);
+                self.claimable_by_address(&address).set(&amount);
+                self.unclaimed_by_round(&address).clear();
+            }
+            TimelockAction::SetNftStartStakeDate(nonce, start_stake_date) => {
+                self.nft_staked_at(&nonce).set(start_stake_date);
+
+                // the streak can't start after the checkpoint
+                if self.nft_stake_start(&nonce).is_empty()
+                    || self.nft_stake_start(&nonce).get() > start_stake_date
+                {
+                    self.nft_stake_start(&nonce).set(start_stake_date);
+                }
+            }
+            TimelockAction::SetTickInterval(tick_interval) => {
+                self.tick_interval().set(tick_interval);
+            }
+        }
     }
 
     // -----------------------
@@ -370,6 +1394,15 @@ pub trait DeainostriNFTStaking {
     #[storage_mapper("storage_compute_index")]
     fn compute_index(&self) -> SingleValueMapper<u64>;
 
+    #[storage_mapper("storage_airdrop_index")]
+    fn airdrop_index(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("storage_expiry_index")]
+    fn expiry_index(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("storage_resolving_reward_amount")]
+    fn resolving_reward_amount(&self) -> SingleValueMapper<BigUint>;
+
     #[view(getTotalPoints)]
     #[storage_mapper("storage_total_points")]
     fn total_points(&self) -> SingleValueMapper<u64>;
@@ -382,10 +1415,79 @@ pub trait DeainostriNFTStaking {
     #[storage_mapper("storage_is_locked")]
     fn is_locked(&self) -> SingleValueMapper<bool>;
 
+    #[view(getIsStakeForRestricted)]
+    #[storage_mapper("storage_is_stake_for_restricted")]
+    fn is_stake_for_restricted(&self) -> SingleValueMapper<bool>;
+
+    #[view(getStakeForCallers)]
+    #[storage_mapper("storage_stake_for_callers")]
+    fn stake_for_callers(&self) -> SetMapper<ManagedAddress>;
+
     #[view(getTickInterval)]
     #[storage_mapper("storage_tick_interval")]
     fn tick_interval(&self) -> SingleValueMapper<u64>;
 
+    #[view(getStreakCurve)]
+    #[storage_mapper("storage_streak_curve")]
+    fn streak_curve(&self) -> SingleValueMapper<ManagedVec<StreakTier>>;
+
+    #[view(getCurrentRewardRound)]
+    #[storage_mapper("storage_current_reward_round")]
+    fn current_reward_round(&self) -> SingleValueMapper<u64>;
+
+    #[view(getRewardRoundDeadline)]
+    #[storage_mapper("storage_reward_round_deadline")]
+    fn reward_round_deadline(&self, round: u64) -> SingleValueMapper<u64>;
+
+    #[view(getClaimPeriod)]
+    #[storage_mapper("storage_claim_period")]
+    fn claim_period(&self) -> SingleValueMapper<u64>;
+
+    #[view(getExpiredRewardsTreasury)]
+    #[storage_mapper("storage_expired_rewards_treasury")]
+    fn expired_rewards_treasury(&self) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getRolledOverRewards)]
+    #[storage_mapper("storage_rolled_over_rewards")]
+    fn rolled_over_rewards(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getRewardDeposits)]
+    #[storage_mapper("storage_reward_deposits")]
+    fn reward_deposits(&self) -> SingleValueMapper<BigUint>;
+
+    #[storage_mapper("storage_reserves_synced")]
+    fn reserves_synced(&self) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("storage_reserve_sync_index")]
+    fn reserve_sync_index(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("storage_reserve_sync_total")]
+    fn reserve_sync_total(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getTotalExpiredRewards)]
+    #[storage_mapper("storage_total_expired_rewards")]
+    fn total_expired_rewards(&self) -> SingleValueMapper<BigUint>;
+
+    #[view(getMinStakeDuration)]
+    #[storage_mapper("storage_min_stake_duration")]
+    fn min_stake_duration(&self) -> SingleValueMapper<u64>;
+
+    #[view(getEarlyUnstakePenalty)]
+    #[storage_mapper("storage_early_unstake_penalty")]
+    fn early_unstake_penalty(&self) -> SingleValueMapper<u64>;
+
+    #[view(getForfeitedPoints)]
+    #[storage_mapper("storage_forfeited_points")]
+    fn forfeited_points(&self) -> SingleValueMapper<u64>;
+
+    #[view(getTotalForfeitedPoints)]
+    #[storage_mapper("storage_total_forfeited_points")]
+    fn total_forfeited_points(&self) -> SingleValueMapper<u64>;
+
+    #[view(getForfeitedPointsByAddress)]
+    #[storage_mapper("storage_forfeited_points_by_address")]
+    fn forfeited_points_by_address(&self, address: &ManagedAddress) -> SingleValueMapper<u64>;
+
     // #[view(getNftTokenId)]
     #[storage_mapper("storage_nft_token_id")]
     fn nft_token_id(&self) -> SingleValueMapper<EgldOrEsdtTokenIdentifier>;
@@ -396,12 +1498,34 @@ pub trait DeainostriNFTStaking {
     // #[storage_mapper("storage_staked_nfts_by_address")]
     // fn nft_map(&self) -> MapMapper<u64, NftInfo<Self::Api>>;
 
+    #[view(getReceiptTokenId)]
+    #[storage_mapper("storage_receipt_token_id")]
+    fn receipt_token_id(&self) -> SingleValueMapper<TokenIdentifier>;
+
+    #[view(getNftByReceipt)]
+    #[storage_mapper("storage_nft_by_receipt")]
+    fn nft_by_receipt(&self, receipt_nonce: u64) -> SingleValueMapper<u64>;
+
+    #[view(getReceiptByNft)]
+    #[storage_mapper("storage_receipt_by_nft")]
+    fn receipt_by_nft(&self, nonce: &u64) -> SingleValueMapper<u64>;
+
     #[storage_mapper("storage_nft_owner")]
     fn nft_owner(&self, nonce: &u64) -> SingleValueMapper<ManagedAddress>;
 
     #[storage_mapper("storage_nft_staked_at")]
     fn nft_staked_at(&self, nonce: &u64) -> SingleValueMapper<u64>;
 
+    #[storage_mapper("storage_nft_stake_start")]
+    fn nft_stake_start(&self, nonce: &u64) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("storage_nft_stake_start_backfilled")]
+    fn nft_stake_start_backfilled(&self, nonce: &u64) -> SingleValueMapper<bool>;
+
+    #[view(getAccruedPointsByNft)]
+    #[storage_mapper("storage_nft_accrued_points")]
+    fn nft_accrued_points(&self, nonce: &u64) -> SingleValueMapper<u64>;
+
     #[view(getPointsByAddress)]
     #[storage_mapper("storage_points_by_address")]
     fn points_by_address(&self, address: &ManagedAddress) -> SingleValueMapper<u64>;
@@ -414,6 +1538,17 @@ pub trait DeainostriNFTStaking {
     #[storage_mapper("storage_claimed_by_address")]
     fn claimed_by_address(&self, address: &ManagedAddress) -> SingleValueMapper<BigUint>;
 
+    #[view(getRewardRecipient)]
+    #[storage_mapper("storage_reward_recipient")]
+    fn reward_recipient(&self, address: &ManagedAddress) -> SingleValueMapper<ManagedAddress>;
+
+    #[view(getClaimDelegate)]
+    #[storage_mapper("storage_claim_delegate")]
+    fn claim_delegate(&self, address: &ManagedAddress) -> SingleValueMapper<ManagedAddress>;
+
+    #[storage_mapper("storage_unclaimed_by_round")]
+    fn unclaimed_by_round(&self, address: &ManagedAddress) -> MapMapper<u64, BigUint>;
+
     #[view(getStakedNfts)]
     #[storage_mapper("storage_nfts_staked_by_address")]
     fn nfts_staked_by_address(&self, address: &ManagedAddress) -> UnorderedSetMapper<u64>;
@@ -421,4 +1556,26 @@ pub trait DeainostriNFTStaking {
     #[view(getStakedAddresses)]
     #[storage_mapper("storage_staked_adresses")]
     fn staked_adresses(&self) -> SetMapper<ManagedAddress>;
+
+    #[view(isReceiptPosition)]
+    #[storage_mapper("storage_is_receipt_position")]
+    fn is_receipt_position(&self, address: &ManagedAddress) -> SingleValueMapper<bool>;
+
+    #[storage_mapper("storage_closed_positions")]
+    fn closed_positions(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    // -----------------------
+    // events
+    // -----------------------
+
+    #[event("transferStake")]
+    fn transfer_stake_event(
+        &self,
+        #[indexed] from: &ManagedAddress,
+        #[indexed] to: &ManagedAddress,
+        nonces: &ManagedVec<u64>,
+    );
+
+    #[event("rewardDeposit")]
+    fn reward_deposit_event(&self, #[indexed] from: &ManagedAddress, amount: &BigUint);
 }
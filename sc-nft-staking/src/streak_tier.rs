@@ -0,0 +1,11 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(
+    TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, ManagedVecItem, Clone,
+)]
+pub struct StreakTier {
+    //
+    pub min_duration: u64,
+    pub multiplier: u64,
+}
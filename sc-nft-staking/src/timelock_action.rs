@@ -0,0 +1,10 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub enum TimelockAction<M: ManagedTypeApi> {
+    //
+    FixStakeReward(ManagedAddress<M>, BigUint<M>),
+    SetNftStartStakeDate(u64, u64),
+    SetTickInterval(u64),
+}
@@ -1,8 +1,10 @@
+use deainostri_roles::{multisig::MultisigModule, timelock::TimelockModule, Role, RolesModule};
 use sc_deainostri_nft_staking::*;
 
 use elrond_wasm::{
+    elrond_codec::multi_types::OptionalValue,
     sc_error, sc_print,
-    types::{Address, SCResult},
+    types::{Address, EsdtLocalRole, ManagedVec, MultiValueEncoded, SCResult},
 };
 
 use elrond_wasm_debug::{
@@ -12,6 +14,7 @@ use elrond_wasm_debug::{
 
 const WASM_PATH: &'static str = "output/sc-deainostri-nft-staking.wasm";
 const CF_TOKEN_ID: &[u8] = b"DEAN-123456";
+const RECEIPT_TOKEN_ID: &[u8] = b"SDEAN-123456";
 
 struct SCTextContext<SCObjBuilder>
 where
@@ -455,7 +458,7 @@ fn stake_test() {
     b_wrapper
         .execute_tx(&charlie, &sc, &rust_biguint!(0u64), |sc| {
             //
-            sc.claim();
+            sc.claim(OptionalValue::None);
 
             print!("## after charlie claim\n");
             print!(
@@ -480,3 +483,1561 @@ fn stake_test() {
         .assert_ok();
     print!("\n");
 }
+
+#[test]
+fn early_unstake_penalty_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+
+    let mut current_timestamp = 1654284522;
+    let day_in_seconds = 86400;
+
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    // -----------------------
+    // 50% penalty if unstaked in less than 30 days
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_early_unstake_penalty(30 * day_in_seconds, 5_000);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    current_timestamp = current_timestamp + 10 * day_in_seconds;
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    // -----------------------
+    // bob unstakes early and forfeits half of his points
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0), |sc| {
+            sc.unstake(2u64);
+
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 5);
+            assert_eq!(sc.forfeited_points().get(), 5);
+            assert_eq!(
+                sc.forfeited_points_by_address(&managed_address!(bob)).get(),
+                5
+            );
+        })
+        .assert_ok();
+
+    // -----------------------
+    // forfeited points go to alice, who is still staking
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+            sc.compute_total_points();
+
+            assert_eq!(sc.points_by_address(&managed_address!(alice)).get(), 15);
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 5);
+            assert_eq!(sc.total_points().get(), 20);
+            assert_eq!(sc.forfeited_points().get(), 0);
+            assert_eq!(sc.total_forfeited_points().get(), 5);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn early_unstake_penalty_after_compute_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+
+    let mut current_timestamp = 1654284522;
+    let day_in_seconds = 86400;
+
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_early_unstake_penalty(30 * day_in_seconds, 5_000);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    current_timestamp = current_timestamp + 10 * day_in_seconds;
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 10);
+            assert_eq!(sc.nft_accrued_points(&2u64).get(), 10);
+        })
+        .assert_ok();
+
+    current_timestamp = current_timestamp + 5 * day_in_seconds;
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    // -----------------------
+    // the penalty covers the points computed before the unstake too
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0), |sc| {
+            assert_eq!(sc.get_early_unstake_penalty_view(2u64), 7);
+
+            sc.unstake(2u64);
+
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 8);
+            assert_eq!(sc.forfeited_points().get(), 7);
+            assert_eq!(sc.nft_accrued_points(&2u64).get(), 0);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.reset_compute_index();
+            sc.compute_all_points();
+            sc.compute_total_points();
+
+            assert_eq!(sc.points_by_address(&managed_address!(alice)).get(), 22);
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 8);
+            assert_eq!(sc.total_points().get(), 30);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn streak_multiplier_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+
+    let start_timestamp = 1654284522;
+    let day_in_seconds = 86400;
+
+    b_wrapper.set_block_timestamp(start_timestamp);
+
+    // -----------------------
+    // 2x points after 5 days of continuous staking
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push((5 * day_in_seconds, 20_000u64).into());
+
+            sc.set_streak_curve(tiers);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // 10 days and a half: 4 ticks at 1x, 6 ticks at 2x
+    // -----------------------
+
+    b_wrapper.set_block_timestamp(start_timestamp + 10 * day_in_seconds + day_in_seconds / 2);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+
+            assert_eq!(sc.points_by_address(&managed_address!(alice)).get(), 16);
+            assert_eq!(
+                sc.nft_staked_at(&1u64).get(),
+                start_timestamp + 10 * day_in_seconds
+            );
+            assert_eq!(sc.get_stake_start_by_nft(1u64), start_timestamp);
+            assert_eq!(sc.get_streak_multiplier_by_nft(1u64), 20_000);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // the half day left over is not lost
+    // -----------------------
+
+    b_wrapper.set_block_timestamp(start_timestamp + 11 * day_in_seconds);
+
+    b_wrapper
+        .execute_query(&sc, |sc| {
+            assert_eq!(sc.get_new_points_by_address(&managed_address!(alice)), 2);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn stake_start_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+
+    let start_timestamp = 1654284522;
+    let day_in_seconds = 86400;
+
+    b_wrapper.set_block_timestamp(start_timestamp);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut tiers = MultiValueEncoded::new();
+            tiers.push((5 * day_in_seconds, 20_000u64).into());
+
+            sc.set_streak_curve(tiers);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+
+            // staked before stake start tracking
+            sc.nft_stake_start(&2u64).clear();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // moving the checkpoint back moves the stake start with it
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_nft_start_stake_date(1u64, start_timestamp - 10 * day_in_seconds);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(start_timestamp + day_in_seconds);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+
+            assert_eq!(
+                sc.get_stake_start_by_nft(1u64),
+                start_timestamp - 10 * day_in_seconds
+            );
+            assert_eq!(sc.get_streak_multiplier_by_nft(1u64), 20_000);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // legacy stakes keep their streak from the first checkpoint on
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+
+            assert_eq!(sc.points_by_address(&managed_address!(alice)).get(), 18);
+            assert_eq!(sc.get_stake_start_by_nft(2u64), start_timestamp);
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(start_timestamp + 6 * day_in_seconds);
+
+    b_wrapper
+        .execute_query(&sc, |sc| {
+            assert_eq!(sc.get_stake_start_by_nft(2u64), start_timestamp);
+            assert_eq!(sc.get_streak_multiplier_by_nft(2u64), 20_000);
+            assert_eq!(sc.get_early_unstake_penalty_view(2u64), 0);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn receipt_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+
+    let current_timestamp = 1654284522;
+    let day_in_seconds = 86400;
+
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    // -----------------------
+    // setup receipt token
+    // -----------------------
+
+    b_wrapper.set_esdt_local_roles(
+        sc.address_ref(),
+        RECEIPT_TOKEN_ID,
+        &[EsdtLocalRole::NftCreate, EsdtLocalRole::NftBurn],
+    );
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_receipt_token_id(managed_token_id!(RECEIPT_TOKEN_ID));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_receipt_token_id(managed_token_id!(b"OTHER-123456"));
+        })
+        .assert_user_error("Receipt token already issued!");
+
+    // -----------------------
+    // alice stakes and receives a receipt
+    // -----------------------
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+
+            assert_eq!(sc.receipt_by_nft(&1u64).get(), 1);
+            assert_eq!(
+                sc.nft_owner(&1u64).get(),
+                sc.get_receipt_position_address(1u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_nft_balance::<u64>(alice, RECEIPT_TOKEN_ID, 1, &rust_biguint!(1), None);
+
+    // -----------------------
+    // alice sells the receipt to bob
+    // -----------------------
+
+    b_wrapper.set_nft_balance(alice, RECEIPT_TOKEN_ID, 1, &rust_biguint!(0), &1u64);
+    b_wrapper.set_nft_balance(bob, RECEIPT_TOKEN_ID, 1, &rust_biguint!(1), &1u64);
+
+    b_wrapper.set_block_timestamp(current_timestamp + 10 * day_in_seconds);
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0), |sc| {
+            sc.unstake(1u64);
+        })
+        .assert_user_error("NFT is not staked by you!");
+
+    b_wrapper.set_egld_balance(bob, &rust_biguint!(10));
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(10), |sc| {
+            sc.unstake(1u64);
+        })
+        .assert_user_error("Only receipts can be sent!");
+
+    // -----------------------
+    // bob unstakes with the receipt and gets the points
+    // -----------------------
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, RECEIPT_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.unstake(1u64);
+
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 10);
+            assert!(sc.nft_by_receipt(1u64).is_empty());
+        })
+        .assert_ok();
+
+    b_wrapper.check_nft_balance::<u64>(bob, CF_TOKEN_ID, 1, &rust_biguint!(1), None);
+    b_wrapper.check_nft_balance::<u64>(bob, RECEIPT_TOKEN_ID, 1, &rust_biguint!(0), None);
+
+    // -----------------------
+    // the closed position leaves the staked addresses with the next round
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let position = sc.get_receipt_position_address(1u64);
+            assert!(sc.staked_adresses().contains(&position));
+
+            sc.reset_compute_index();
+
+            assert!(!sc.staked_adresses().contains(&position));
+            assert!(sc.staked_adresses().contains(&managed_address!(bob)));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn transfer_stake_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let bob = &cf_setup.bob;
+    let zeta = &cf_setup.zeta;
+
+    let current_timestamp = 1654284522;
+    let day_in_seconds = 86400;
+
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 20, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 5 * day_in_seconds);
+
+    // -----------------------
+    // bob moves both nfts to his new wallet
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0), |sc| {
+            let mut nonces = ManagedVec::new();
+            nonces.push(2u64);
+            nonces.push(20u64);
+
+            sc.transfer_stake(nonces, managed_address!(zeta));
+
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 10);
+            assert_eq!(sc.nft_owner(&2u64).get(), managed_address!(zeta));
+            assert_eq!(sc.nft_owner(&20u64).get(), managed_address!(zeta));
+            assert_eq!(sc.nfts_staked_by_address(&managed_address!(bob)).len(), 0);
+            assert_eq!(sc.nfts_staked_by_address(&managed_address!(zeta)).len(), 2);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0), |sc| {
+            let mut nonces = ManagedVec::new();
+            nonces.push(2u64);
+
+            sc.transfer_stake(nonces, managed_address!(owner));
+        })
+        .assert_user_error("NFT is not staked by you!");
+
+    // -----------------------
+    // new points go to the new wallet
+    // -----------------------
+
+    b_wrapper.set_block_timestamp(current_timestamp + 10 * day_in_seconds);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 10);
+            assert_eq!(sc.points_by_address(&managed_address!(zeta)).get(), 10);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn transfer_stake_then_unstake_early_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let bob = &cf_setup.bob;
+    let zeta = &cf_setup.zeta;
+
+    let current_timestamp = 1654284522;
+    let day_in_seconds = 86400;
+
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_early_unstake_penalty(30 * day_in_seconds, 5_000);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // bob is past the early unstake window when he transfers
+    // -----------------------
+
+    b_wrapper.set_block_timestamp(current_timestamp + 40 * day_in_seconds);
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0), |sc| {
+            let mut nonces = ManagedVec::new();
+            nonces.push(2u64);
+
+            sc.transfer_stake(nonces, managed_address!(zeta));
+
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 40);
+            assert_eq!(
+                sc.get_stake_start_by_nft(2u64),
+                current_timestamp + 40 * day_in_seconds
+            );
+        })
+        .assert_ok();
+
+    // -----------------------
+    // zeta's stake starts at the transfer, unstaking now is early
+    // -----------------------
+
+    b_wrapper.set_block_timestamp(current_timestamp + 45 * day_in_seconds);
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(0), |sc| {
+            sc.unstake(2u64);
+
+            assert_eq!(sc.points_by_address(&managed_address!(zeta)).get(), 3);
+            assert_eq!(sc.forfeited_points().get(), 2);
+            assert_eq!(sc.points_by_address(&managed_address!(bob)).get(), 40);
+        })
+        .assert_ok();
+
+    b_wrapper.check_nft_balance::<u64>(zeta, CF_TOKEN_ID, 2, &rust_biguint!(1), None);
+}
+
+#[test]
+fn stake_for_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let charlie = &cf_setup.charlie;
+
+    b_wrapper.set_block_timestamp(1654284522);
+
+    // -----------------------
+    // only allowed callers can stake for others
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_stake_for_restricted(true);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(charlie, &sc, CF_TOKEN_ID, 3, &rust_biguint!(1), |sc| {
+            sc.stake_for(managed_address!(alice));
+        })
+        .assert_user_error("Caller is not allowed to stake for others!");
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.add_stake_for_caller(managed_address!(charlie));
+        })
+        .assert_ok();
+
+    // -----------------------
+    // charlie gifts a staked nft to alice
+    // -----------------------
+
+    b_wrapper
+        .execute_esdt_transfer(charlie, &sc, CF_TOKEN_ID, 3, &rust_biguint!(1), |sc| {
+            sc.stake_for(managed_address!(alice));
+
+            assert_eq!(sc.nft_owner(&3u64).get(), managed_address!(alice));
+            assert!(sc
+                .nfts_staked_by_address(&managed_address!(alice))
+                .contains(&3u64));
+            assert_eq!(
+                sc.nfts_staked_by_address(&managed_address!(charlie)).len(),
+                0
+            );
+        })
+        .assert_ok();
+}
+
+#[test]
+fn claim_recipient_and_delegate_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+    let charlie = &cf_setup.charlie;
+    let zeta = &cf_setup.zeta;
+
+    b_wrapper.set_egld_balance(sc.address_ref(), &rust_biguint!(1_000u64));
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.fix_stake_reward(managed_address!(alice), managed_biguint!(1_000u64));
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(86400);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // alice sends rewards to zeta and lets charlie claim them
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_reward_recipient(OptionalValue::Some(managed_address!(zeta)));
+            sc.set_claim_delegate(OptionalValue::Some(managed_address!(charlie)));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim(OptionalValue::Some(managed_address!(alice)));
+        })
+        .assert_user_error("Caller is not allowed to claim for this address!");
+
+    b_wrapper
+        .execute_tx(charlie, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim(OptionalValue::Some(managed_address!(alice)));
+
+            assert_eq!(
+                sc.claimed_by_address(&managed_address!(alice)).get(),
+                managed_biguint!(1_000u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(zeta, &rust_biguint!(1_000u64));
+    b_wrapper.check_egld_balance(charlie, &rust_biguint!(0u64));
+}
+
+#[test]
+fn airdrop_rewards_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+
+    b_wrapper.set_egld_balance(sc.address_ref(), &rust_biguint!(1_000u64));
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.fix_stake_reward(managed_address!(alice), managed_biguint!(400u64));
+            sc.fix_stake_reward(managed_address!(bob), managed_biguint!(600u64));
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(86400);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+            sc.execute_action(2);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // rewards are pushed to every staker
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.airdrop_rewards();
+
+            let (index, total) = sc.get_airdrop_progress().into_tuple();
+            assert_eq!(index, 2);
+            assert_eq!(total, 2);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(400u64));
+    b_wrapper.check_egld_balance(bob, &rust_biguint!(600u64));
+}
+
+#[test]
+fn airdrop_credits_contract_recipients_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+
+    let recipient = b_wrapper
+        .create_sc_account(
+            &rust_biguint!(0u64),
+            Some(owner),
+            sc_deainostri_nft_staking::contract_obj,
+            WASM_PATH,
+        )
+        .address_ref()
+        .clone();
+
+    b_wrapper.set_egld_balance(sc.address_ref(), &rust_biguint!(1_000u64));
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+            sc.set_reward_recipient(OptionalValue::Some(managed_address!(&recipient)));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.fix_stake_reward(managed_address!(alice), managed_biguint!(400u64));
+            sc.fix_stake_reward(managed_address!(bob), managed_biguint!(600u64));
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(86400);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+            sc.execute_action(2);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // the contract recipient gets alice's rewards as claimable
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.airdrop_rewards();
+
+            assert_eq!(sc.claimable_by_address(&managed_address!(alice)).get(), 0);
+            assert_eq!(
+                sc.claimable_by_address(&managed_address!(&recipient)).get(),
+                managed_biguint!(400u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(bob, &rust_biguint!(600u64));
+    b_wrapper.check_egld_balance(&recipient, &rust_biguint!(0u64));
+
+    b_wrapper
+        .execute_tx(&recipient, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim(OptionalValue::None);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(&recipient, &rust_biguint!(400u64));
+}
+
+#[test]
+fn airdrop_skips_receipt_positions_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+
+    b_wrapper.set_egld_balance(sc.address_ref(), &rust_biguint!(1_000u64));
+
+    b_wrapper.set_esdt_local_roles(
+        sc.address_ref(),
+        RECEIPT_TOKEN_ID,
+        &[EsdtLocalRole::NftCreate, EsdtLocalRole::NftBurn],
+    );
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_receipt_token_id(managed_token_id!(RECEIPT_TOKEN_ID));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let position = sc.get_receipt_position_address(1u64);
+
+            sc.fix_stake_reward(position, managed_biguint!(400u64));
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(86400);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // nobody controls the position address, rewards wait for the receipt
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.airdrop_rewards();
+
+            let position = sc.get_receipt_position_address(1u64);
+            assert_eq!(
+                sc.claimable_by_address(&position).get(),
+                managed_biguint!(400u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(sc.address_ref(), &rust_biguint!(1_000u64));
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, RECEIPT_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.claim_with_receipt();
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(400u64));
+}
+
+#[test]
+fn reward_expiry_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+
+    let current_timestamp = 1654284522;
+    let day_in_seconds = 86400;
+
+    b_wrapper.set_block_timestamp(current_timestamp);
+
+    // -----------------------
+    // rewards can be claimed for 7 days
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_reward_expiry(7 * day_in_seconds, OptionalValue::None);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper.set_block_timestamp(current_timestamp + 10 * day_in_seconds);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+            sc.compute_total_points();
+            sc.reset_compute_index();
+            sc.compute_percentages();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(1_000u64), |sc| {
+            sc.add_stake_reward();
+
+            assert_eq!(sc.current_reward_round().get(), 1);
+            assert_eq!(
+                sc.reward_round_deadline(1).get(),
+                current_timestamp + 17 * day_in_seconds
+            );
+
+            let expiries: Vec<_> = sc
+                .get_upcoming_expiries(managed_address!(alice))
+                .into_iter()
+                .map(|expiry| expiry.into_tuple())
+                .collect();
+            assert_eq!(
+                expiries,
+                vec![(
+                    current_timestamp + 17 * day_in_seconds,
+                    managed_biguint!(500u64)
+                )]
+            );
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim(OptionalValue::None);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // alice never claims, her rewards roll over
+    // -----------------------
+
+    b_wrapper.set_block_timestamp(current_timestamp + 18 * day_in_seconds);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.expire_rewards();
+
+            assert_eq!(
+                sc.claimable_by_address(&managed_address!(alice)).get(),
+                managed_biguint!(0u64)
+            );
+            assert_eq!(sc.rolled_over_rewards().get(), managed_biguint!(500u64));
+            assert_eq!(sc.total_expired_rewards().get(), managed_biguint!(500u64));
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(bob, &rust_biguint!(500u64));
+}
+
+#[test]
+fn roles_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+
+    // -----------------------
+    // alice runs the operations bot
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.grant_role(Role::Operator, managed_address!(alice));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.lock();
+        })
+        .assert_user_error("Caller does not have the required role!");
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+        })
+        .assert_user_error("Caller does not have the required role!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.grant_role(Role::Operator, managed_address!(bob));
+        })
+        .assert_user_error("Caller is not allowed to manage this role!");
+
+    // -----------------------
+    // alice steps down
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.renounce_role(Role::Operator);
+
+            assert!(!sc.has_role(Role::Operator, managed_address!(alice)));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.compute_all_points();
+        })
+        .assert_user_error("Caller does not have the required role!");
+}
+
+#[test]
+fn timelock_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+
+    let delay = 86400u64;
+    b_wrapper.set_block_timestamp(1000);
+
+    // -----------------------
+    // a day by default, never below an hour
+    // -----------------------
+
+    b_wrapper
+        .execute_query(&sc, |sc| {
+            assert_eq!(sc.timelock_delay().get(), delay);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_timelock_delay(0);
+        })
+        .assert_user_error("Timelock delay is too short!");
+
+    // -----------------------
+    // overrides are queued instead of applied
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.fix_stake_reward(managed_address!(alice), managed_biguint!(500));
+            sc.set_tick_interval(60);
+
+            assert_eq!(sc.claimable_by_address(&managed_address!(alice)).get(), 0);
+            assert_eq!(sc.get_pending_actions().len(), 2);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+        })
+        .assert_user_error("Action is still timelocked!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.cancel_action(2);
+        })
+        .assert_user_error("Caller does not have the required role!");
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.cancel_action(2);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // once the delay passed the action can be executed
+    // -----------------------
+
+    b_wrapper.set_block_timestamp(1000 + delay);
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(1);
+
+            assert_eq!(
+                sc.claimable_by_address(&managed_address!(alice)).get(),
+                managed_biguint!(500)
+            );
+            assert_eq!(sc.get_pending_actions().len(), 0);
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_action(2);
+        })
+        .assert_user_error("Action not found!");
+
+    // -----------------------
+    // lowering the delay is timelocked as well
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.set_timelock_delay(3600);
+
+            assert_eq!(sc.timelock_delay().get(), delay);
+            assert_eq!(sc.get_pending_actions().len(), 1);
+        })
+        .assert_ok();
+}
+
+#[test]
+fn multisig_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+    let charlie = &cf_setup.charlie;
+    let zeta = &cf_setup.zeta;
+
+    b_wrapper.set_egld_balance(sc.address_ref(), &rust_biguint!(1_000u64));
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut signers = MultiValueEncoded::new();
+            signers.push(managed_address!(alice));
+            signers.push(managed_address!(bob));
+            signers.push(managed_address!(charlie));
+
+            sc.setup_multisig(2, signers);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // a withdrawal needs two of the three signers
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(0u64), |sc| {
+            sc.propose_withdrawal(
+                managed_address!(zeta),
+                managed_biguint!(400),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Caller is not a signer!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            let proposal_id = sc.propose_withdrawal(
+                managed_address!(zeta),
+                managed_biguint!(400),
+                OptionalValue::None,
+            );
+
+            assert_eq!(proposal_id, 1);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(zeta, &rust_biguint!(0u64));
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.approve_proposal(1);
+        })
+        .assert_user_error("Proposal already approved!");
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.approve_proposal(1);
+
+            assert_eq!(sc.get_pending_proposals().len(), 0);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(zeta, &rust_biguint!(400u64));
+
+    // -----------------------
+    // removed signers no longer count towards the threshold
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.propose_withdrawal(
+                managed_address!(zeta),
+                managed_biguint!(600),
+                OptionalValue::None,
+            );
+            sc.propose_remove_signer(managed_address!(alice));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(charlie, &sc, &rust_biguint!(0u64), |sc| {
+            sc.approve_proposal(3);
+
+            assert!(!sc.signers().contains(&managed_address!(alice)));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.execute_proposal(2);
+        })
+        .assert_user_error("Not enough approvals!");
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.approve_proposal(2);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(zeta, &rust_biguint!(400u64));
+
+    b_wrapper
+        .execute_tx(charlie, &sc, &rust_biguint!(0u64), |sc| {
+            sc.approve_proposal(2);
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(zeta, &rust_biguint!(1_000u64));
+
+    // -----------------------
+    // the threshold can't exceed the signers
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(bob, &sc, &rust_biguint!(0u64), |sc| {
+            sc.propose_remove_signer(managed_address!(charlie));
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(charlie, &sc, &rust_biguint!(0u64), |sc| {
+            sc.approve_proposal(4);
+        })
+        .assert_user_error("Invalid threshold!");
+}
+
+#[test]
+fn deposit_reward_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let zeta = &cf_setup.zeta;
+
+    b_wrapper.set_egld_balance(owner, &rust_biguint!(200u64));
+    b_wrapper.set_egld_balance(zeta, &rust_biguint!(300u64));
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    // -----------------------
+    // anyone, e.g. the minter, can deposit rewards
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(0u64), |sc| {
+            sc.deposit_reward();
+        })
+        .assert_user_error("Invalid amount!");
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(300u64), |sc| {
+            sc.deposit_reward();
+
+            assert_eq!(sc.reward_deposits().get(), managed_biguint!(300u64));
+        })
+        .assert_ok();
+
+    // -----------------------
+    // deposits join the next reward round
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(200u64), |sc| {
+            sc.points_by_address(&managed_address!(alice)).set(1000000);
+
+            sc.add_stake_reward();
+
+            assert_eq!(
+                sc.claimable_by_address(&managed_address!(alice)).get(),
+                managed_biguint!(500u64)
+            );
+            assert_eq!(sc.reward_deposits().get(), managed_biguint!(0u64));
+        })
+        .assert_ok();
+}
+
+#[test]
+fn multisig_reserved_funds_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let zeta = &cf_setup.zeta;
+
+    b_wrapper.set_egld_balance(zeta, &rust_biguint!(300u64));
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut signers = MultiValueEncoded::new();
+            signers.push(managed_address!(zeta));
+
+            sc.setup_multisig(1, signers);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // unpaid deposits and claimable rewards stay in the contract
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(300u64), |sc| {
+            sc.deposit_reward();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(200u64), |sc| {
+            sc.points_by_address(&managed_address!(alice)).set(1000000);
+
+            sc.add_stake_reward();
+
+            assert_eq!(
+                sc.get_reserved_funds(OptionalValue::None),
+                managed_biguint!(500u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.set_egld_balance(sc.address_ref(), &rust_biguint!(700u64));
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(0u64), |sc| {
+            sc.propose_withdrawal(
+                managed_address!(zeta),
+                managed_biguint!(201),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Not enough funds!");
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(0u64), |sc| {
+            sc.propose_withdrawal(
+                managed_address!(zeta),
+                managed_biguint!(200),
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    // -----------------------
+    // claiming frees the reserve
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim(OptionalValue::None);
+
+            assert_eq!(
+                sc.get_reserved_funds(OptionalValue::None),
+                managed_biguint!(0u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(500u64));
+    b_wrapper.check_egld_balance(zeta, &rust_biguint!(200u64));
+}
+
+#[test]
+fn sync_reserved_funds_test() {
+    let mut cf_setup = setup_sc(sc_deainostri_nft_staking::contract_obj);
+    let b_wrapper = &mut cf_setup.blockchain_wrapper;
+    let sc = &mut cf_setup.cf_wrapper;
+
+    let owner = &cf_setup.owner_address;
+    let alice = &cf_setup.alice;
+    let bob = &cf_setup.bob;
+    let zeta = &cf_setup.zeta;
+
+    b_wrapper
+        .execute_esdt_transfer(alice, &sc, CF_TOKEN_ID, 1, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_esdt_transfer(bob, &sc, CF_TOKEN_ID, 2, &rust_biguint!(1), |sc| {
+            sc.stake();
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            let mut signers = MultiValueEncoded::new();
+            signers.push(managed_address!(zeta));
+
+            sc.setup_multisig(1, signers);
+        })
+        .assert_ok();
+
+    // -----------------------
+    // rewards credited before the upgrade were never reserved
+    // -----------------------
+
+    b_wrapper.set_egld_balance(sc.address_ref(), &rust_biguint!(500u64));
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claimable_by_address(&managed_address!(alice))
+                .set(managed_biguint!(300u64));
+            sc.claimable_by_address(&managed_address!(bob))
+                .set(managed_biguint!(100u64));
+            sc.reserves_synced().clear();
+
+            // upgrade
+            sc.init();
+
+            assert!(sc.reserves_pending().get());
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(0u64), |sc| {
+            sc.propose_withdrawal(
+                managed_address!(zeta),
+                managed_biguint!(100),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Reserved funds are not synced yet!");
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim(OptionalValue::None);
+        })
+        .assert_user_error("Reserved funds are not synced yet!");
+
+    // -----------------------
+    // the sync books every claimable
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.sync_reserved_funds();
+
+            assert_eq!(
+                sc.get_reserved_funds(OptionalValue::None),
+                managed_biguint!(400u64)
+            );
+            assert!(!sc.reserves_pending().get());
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.sync_reserved_funds();
+        })
+        .assert_user_error("Reserved funds are already synced!");
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(0u64), |sc| {
+            sc.propose_withdrawal(
+                managed_address!(zeta),
+                managed_biguint!(101),
+                OptionalValue::None,
+            );
+        })
+        .assert_user_error("Not enough funds!");
+
+    b_wrapper
+        .execute_tx(zeta, &sc, &rust_biguint!(0u64), |sc| {
+            sc.propose_withdrawal(
+                managed_address!(zeta),
+                managed_biguint!(100),
+                OptionalValue::None,
+            );
+        })
+        .assert_ok();
+
+    // -----------------------
+    // later upgrades don't need another sync
+    // -----------------------
+
+    b_wrapper
+        .execute_tx(&owner, &sc, &rust_biguint!(0u64), |sc| {
+            sc.init();
+
+            assert!(!sc.reserves_pending().get());
+        })
+        .assert_ok();
+
+    b_wrapper
+        .execute_tx(alice, &sc, &rust_biguint!(0u64), |sc| {
+            sc.claim(OptionalValue::None);
+
+            assert_eq!(
+                sc.get_reserved_funds(OptionalValue::None),
+                managed_biguint!(100u64)
+            );
+        })
+        .assert_ok();
+
+    b_wrapper.check_egld_balance(alice, &rust_biguint!(300u64));
+    b_wrapper.check_egld_balance(zeta, &rust_biguint!(100u64));
+}
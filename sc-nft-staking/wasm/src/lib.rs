@@ -7,32 +7,102 @@
 elrond_wasm_node::wasm_endpoints! {
     sc_deainostri_nft_staking
     (
+        callBack
+        addStakeForCaller
         addStakeReward
+        airdropRewards
+        approveProposal
+        cancelAction
         claim
+        claimWithReceipt
         computePercentages
         computePoints
         computeTotalPoints
+        depositReward
+        discardProposal
+        executeAction
+        executeProposal
+        expireRewards
         fixStakeReward
+        getAccruedPointsByNft
+        getAirdropProgress
+        getApprovals
+        getClaimDelegate
+        getClaimPeriod
         getClaimableByAddress
         getClaimedByAddress
         getComputeIndex
+        getCurrentRewardRound
+        getEarlyUnstakePenalty
+        getEarlyUnstakePenaltyByNft
+        getExpiredRewardsTreasury
+        getForfeitedPoints
+        getForfeitedPointsByAddress
         getIsLocked
+        getIsStakeForRestricted
+        getMinStakeDuration
         getNewPointsByAddress
+        getNftByReceipt
+        getPendingActions
+        getPendingProposals
         getPointsByAddress
+        getReceiptByNft
+        getReceiptPositionAddress
+        getReceiptTokenId
+        getReservedFunds
+        getReservesPending
+        getRewardDeposits
+        getRewardRecipient
+        getRewardRoundDeadline
+        getRoleMembers
+        getRolledOverRewards
+        getSigners
+        getStakeForCallers
+        getStakeStartByNft
         getStakedAddresses
         getStakedNfts
+        getStreakCurve
+        getStreakMultiplierByNft
+        getThreshold
         getTickInterval
+        getTimelockDelay
+        getTotalExpiredRewards
+        getTotalForfeitedPoints
         getTotalPoints
+        getUpcomingExpiries
+        grantRole
+        hasRole
+        isReceiptPosition
+        issueReceiptToken
         lock
+        proposeAddSigner
+        proposeChangeThreshold
+        proposeRemoveSigner
+        proposeWithdrawal
+        removeStakeForCaller
+        renounceRole
         resetComputeIndex
         resolveStakeReward
+        revokeRole
+        setClaimDelegate
+        setEarlyUnstakePenalty
         setNftStartStakeDate
         setNftTokenId
+        setReceiptLocalRoles
+        setReceiptTokenId
+        setRewardExpiry
+        setRewardRecipient
+        setStakeForRestricted
+        setStreakCurve
         setTickInterval
+        setTimelockDelay
+        setupMultisig
         stake
+        stakeFor
+        syncReservedFunds
+        transferStake
+        unapproveProposal
         unlock
         unstake
     )
 }
-
-elrond_wasm_node::wasm_empty_callback! {}
@@ -0,0 +1,123 @@
+#![no_std]
+
+#[cfg(feature = "elrond-wasm-0-31")]
+extern crate elrond_wasm_0_31 as elrond_wasm;
+
+#[cfg(all(feature = "elrond-wasm-0-32", not(feature = "elrond-wasm-0-31")))]
+extern crate elrond_wasm_0_32 as elrond_wasm;
+
+#[cfg(not(any(feature = "elrond-wasm-0-31", feature = "elrond-wasm-0-32")))]
+compile_error!("Select the elrond-wasm version with one of the elrond-wasm-0-3x features!");
+
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+pub mod multisig;
+pub mod timelock;
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone, Copy)]
+pub enum Role {
+    Admin,
+    Operator,
+    WhitelistManager,
+    Treasurer,
+}
+
+// Roles shared by the minter and the staking contracts. The owner can do
+// everything, admins can do everything a role can and manage non-admin roles.
+#[elrond_wasm::module]
+pub trait RolesModule {
+    // -----------------------
+    // role methods
+    // -----------------------
+
+    #[endpoint(grantRole)]
+    fn grant_role(&self, role: Role, address: ManagedAddress) {
+        self.require_can_manage_role(role);
+
+        require!(!address.is_zero(), "Invalid address!");
+
+        if self.role_members(role).insert(address.clone()) {
+            self.role_granted_event(role, &address, &self.blockchain().get_caller());
+        }
+    }
+
+    #[endpoint(revokeRole)]
+    fn revoke_role(&self, role: Role, address: ManagedAddress) {
+        self.require_can_manage_role(role);
+
+        if self.role_members(role).swap_remove(&address) {
+            self.role_revoked_event(role, &address, &self.blockchain().get_caller());
+        }
+    }
+
+    #[endpoint(renounceRole)]
+    fn renounce_role(&self, role: Role) {
+        let caller = self.blockchain().get_caller();
+
+        require!(
+            self.role_members(role).swap_remove(&caller),
+            "Caller does not have this role!"
+        );
+
+        self.role_renounced_event(role, &caller);
+    }
+
+    fn require_can_manage_role(&self, role: Role) {
+        let caller = self.blockchain().get_caller();
+
+        // only the owner can hand out admin rights
+        require!(
+            caller == self.blockchain().get_owner_address()
+                || (role != Role::Admin && self.role_members(Role::Admin).contains(&caller)),
+            "Caller is not allowed to manage this role!"
+        );
+    }
+
+    fn require_role(&self, role: Role) {
+        let caller = self.blockchain().get_caller();
+
+        require!(
+            caller == self.blockchain().get_owner_address()
+                || self.role_members(Role::Admin).contains(&caller)
+                || self.role_members(role).contains(&caller),
+            "Caller does not have the required role!"
+        );
+    }
+
+    #[view(hasRole)]
+    fn has_role(&self, role: Role, address: ManagedAddress) -> bool {
+        self.role_members(role).contains(&address)
+    }
+
+    // -----------------------
+    // storage
+    // -----------------------
+
+    #[view(getRoleMembers)]
+    #[storage_mapper("roleMembers")]
+    fn role_members(&self, role: Role) -> UnorderedSetMapper<ManagedAddress>;
+
+    // -----------------------
+    // events
+    // -----------------------
+
+    #[event("roleGranted")]
+    fn role_granted_event(
+        &self,
+        #[indexed] role: Role,
+        #[indexed] address: &ManagedAddress,
+        granted_by: &ManagedAddress,
+    );
+
+    #[event("roleRevoked")]
+    fn role_revoked_event(
+        &self,
+        #[indexed] role: Role,
+        #[indexed] address: &ManagedAddress,
+        revoked_by: &ManagedAddress,
+    );
+
+    #[event("roleRenounced")]
+    fn role_renounced_event(&self, #[indexed] role: Role, #[indexed] address: &ManagedAddress);
+}
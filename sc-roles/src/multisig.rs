@@ -0,0 +1,378 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub enum MultisigAction<M: ManagedTypeApi> {
+    // destination, token (EGLD when missing), amount
+    Withdraw(ManagedAddress<M>, Option<TokenIdentifier<M>>, BigUint<M>),
+    AddSigner(ManagedAddress<M>),
+    RemoveSigner(ManagedAddress<M>),
+    ChangeThreshold(u32),
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub struct Proposal<M: ManagedTypeApi> {
+    pub action: MultisigAction<M>,
+    pub proposer: ManagedAddress<M>,
+}
+
+// M-of-N approval flow for moving funds out of the contract. Changes to the
+// signers and to the threshold go through the same proposals.
+#[elrond_wasm::module]
+pub trait MultisigModule {
+    // -----------------------
+    // setup methods
+    // -----------------------
+
+    #[only_owner]
+    #[endpoint(setupMultisig)]
+    fn setup_multisig(&self, threshold: u32, signers: MultiValueEncoded<ManagedAddress>) {
+        require!(self.signers().is_empty(), "Multisig already set up!");
+
+        for signer in signers.into_iter() {
+            require!(!signer.is_zero(), "Invalid address!");
+
+            self.signers().insert(signer);
+        }
+
+        self.require_valid_threshold(threshold, self.signers().len());
+        self.threshold().set(threshold);
+    }
+
+    // -----------------------
+    // proposal methods
+    // -----------------------
+
+    #[endpoint(proposeWithdrawal)]
+    fn propose_withdrawal(
+        &self,
+        destination: ManagedAddress,
+        amount: BigUint,
+        token: OptionalValue<TokenIdentifier>,
+    ) -> u64 {
+        require!(!destination.is_zero(), "Invalid address!");
+        require!(amount > 0, "Invalid amount!");
+        self.require_reserves_synced();
+
+        self.propose(MultisigAction::Withdraw(
+            destination,
+            token.into_option(),
+            amount,
+        ))
+    }
+
+    #[endpoint(proposeAddSigner)]
+    fn propose_add_signer(&self, signer: ManagedAddress) -> u64 {
+        require!(!signer.is_zero(), "Invalid address!");
+
+        self.propose(MultisigAction::AddSigner(signer))
+    }
+
+    #[endpoint(proposeRemoveSigner)]
+    fn propose_remove_signer(&self, signer: ManagedAddress) -> u64 {
+        self.propose(MultisigAction::RemoveSigner(signer))
+    }
+
+    #[endpoint(proposeChangeThreshold)]
+    fn propose_change_threshold(&self, threshold: u32) -> u64 {
+        self.propose(MultisigAction::ChangeThreshold(threshold))
+    }
+
+    // the proposal is executed as soon as the threshold is reached
+    #[endpoint(approveProposal)]
+    fn approve_proposal(&self, proposal_id: u64) {
+        let caller = self.require_signer();
+
+        require!(
+            self.proposals().contains_key(&proposal_id),
+            "Proposal not found!"
+        );
+        require!(
+            self.approvals(proposal_id).insert(caller.clone()),
+            "Proposal already approved!"
+        );
+
+        self.proposal_approved_event(proposal_id, &caller);
+
+        if self.get_approval_count(proposal_id) >= self.threshold().get() {
+            self.perform_proposal(proposal_id);
+        }
+    }
+
+    #[endpoint(unapproveProposal)]
+    fn unapprove_proposal(&self, proposal_id: u64) {
+        let caller = self.require_signer();
+
+        require!(
+            self.approvals(proposal_id).swap_remove(&caller),
+            "Proposal not approved!"
+        );
+
+        self.proposal_unapproved_event(proposal_id, &caller);
+    }
+
+    // needed when the threshold was lowered after the last approval
+    #[endpoint(executeProposal)]
+    fn execute_proposal(&self, proposal_id: u64) {
+        self.require_signer();
+
+        require!(
+            self.proposals().contains_key(&proposal_id),
+            "Proposal not found!"
+        );
+        require!(
+            self.get_approval_count(proposal_id) >= self.threshold().get(),
+            "Not enough approvals!"
+        );
+
+        self.perform_proposal(proposal_id);
+    }
+
+    #[endpoint(discardProposal)]
+    fn discard_proposal(&self, proposal_id: u64) {
+        let caller = self.require_signer();
+
+        let proposal = self.proposals().get(&proposal_id);
+        require!(proposal.is_some(), "Proposal not found!");
+        require!(
+            proposal.unwrap().proposer == caller,
+            "Only the proposer can discard!"
+        );
+
+        self.remove_proposal(proposal_id);
+        self.proposal_discarded_event(proposal_id);
+    }
+
+    fn propose(&self, action: MultisigAction<Self::Api>) -> u64 {
+        let caller = self.require_signer();
+
+        let proposal_id = self.last_proposal_id().get() + 1;
+        self.last_proposal_id().set(proposal_id);
+
+        self.proposal_created_event(proposal_id, &caller, &action);
+
+        self.proposals().insert(
+            proposal_id,
+            Proposal {
+                action,
+                proposer: caller.clone(),
+            },
+        );
+
+        // proposing counts as the first approval
+        self.approvals(proposal_id).insert(caller);
+
+        if self.get_approval_count(proposal_id) >= self.threshold().get() {
+            self.perform_proposal(proposal_id);
+        }
+
+        proposal_id
+    }
+
+    fn perform_proposal(&self, proposal_id: u64) {
+        let proposal = self.proposals().get(&proposal_id).unwrap();
+        self.remove_proposal(proposal_id);
+
+        match proposal.action {
+            MultisigAction::Withdraw(destination, token, amount) => {
+                self.send_withdrawal(&destination, token, &amount);
+            }
+            MultisigAction::AddSigner(signer) => {
+                require!(self.signers().insert(signer), "Already a signer!");
+            }
+            MultisigAction::RemoveSigner(signer) => {
+                require!(self.signers().swap_remove(&signer), "Not a signer!");
+
+                self.require_valid_threshold(self.threshold().get(), self.signers().len());
+            }
+            MultisigAction::ChangeThreshold(threshold) => {
+                self.require_valid_threshold(threshold, self.signers().len());
+                self.threshold().set(threshold);
+            }
+        }
+
+        self.proposal_executed_event(proposal_id);
+    }
+
+    fn send_withdrawal(
+        &self,
+        destination: &ManagedAddress,
+        token: Option<TokenIdentifier>,
+        amount: &BigUint,
+    ) {
+        require!(
+            &self.get_free_balance(&token) >= amount,
+            "Not enough funds!"
+        );
+
+        match token {
+            Some(token) => {
+                let mut payments = ManagedVec::new();
+                payments.push(EsdtTokenPayment::new(token, 0, amount.clone()));
+
+                self.send().direct_multi(destination, &payments, &[]);
+            }
+            None => {
+                self.send().direct_egld(destination, amount, &[]);
+            }
+        }
+    }
+
+    // -----------------------
+    // reserve methods
+    // -----------------------
+
+    // funds owed to others (rewards, payouts, refunds) can't be withdrawn,
+    // the contract books them here whenever they change
+    fn reserve_funds(&self, token: Option<TokenIdentifier>, amount: &BigUint) {
+        self.require_reserves_synced();
+
+        self.reserved_funds(&token)
+            .update(|reserved| *reserved += amount);
+    }
+
+    // funds owed before the reserve existed were never booked, so the
+    // reserve never goes below zero
+    fn release_funds(&self, token: Option<TokenIdentifier>, amount: &BigUint) {
+        self.require_reserves_synced();
+
+        self.reserved_funds(&token).update(|reserved| {
+            if &*reserved > amount {
+                *reserved -= amount;
+            } else {
+                *reserved = BigUint::zero();
+            }
+        });
+    }
+
+    fn get_free_balance(&self, token: &Option<TokenIdentifier>) -> BigUint {
+        self.require_reserves_synced();
+
+        let sc_address = self.blockchain().get_sc_address();
+        let balance = match token {
+            Some(token) => self.blockchain().get_esdt_balance(&sc_address, token, 0),
+            None => self.blockchain().get_balance(&sc_address),
+        };
+        let reserved = self.reserved_funds(token).get();
+
+        if balance > reserved {
+            balance - reserved
+        } else {
+            BigUint::zero()
+        }
+    }
+
+    // contracts upgraded with funds owed from before the reserve existed
+    // book them first, until then the reserve can't be trusted or changed
+    fn require_reserves_synced(&self) {
+        require!(
+            !self.reserves_pending().get(),
+            "Reserved funds are not synced yet!"
+        );
+    }
+
+    #[view(getReservedFunds)]
+    fn get_reserved_funds(&self, token: OptionalValue<TokenIdentifier>) -> BigUint {
+        self.reserved_funds(&token.into_option()).get()
+    }
+
+    fn remove_proposal(&self, proposal_id: u64) {
+        self.proposals().remove(&proposal_id);
+        self.approvals(proposal_id).clear();
+    }
+
+    fn require_signer(&self) -> ManagedAddress {
+        let caller = self.blockchain().get_caller();
+
+        require!(self.signers().contains(&caller), "Caller is not a signer!");
+
+        caller
+    }
+
+    fn require_valid_threshold(&self, threshold: u32, signers_count: usize) {
+        require!(
+            threshold > 0 && threshold as usize <= signers_count,
+            "Invalid threshold!"
+        );
+    }
+
+    // approvals of removed signers no longer count
+    fn get_approval_count(&self, proposal_id: u64) -> u32 {
+        let signers = self.signers();
+
+        self.approvals(proposal_id)
+            .iter()
+            .filter(|approver| signers.contains(approver))
+            .count() as u32
+    }
+
+    #[view(getPendingProposals)]
+    fn get_pending_proposals(
+        &self,
+    ) -> MultiValueEncoded<MultiValue3<u64, Proposal<Self::Api>, u32>> {
+        let mut result = MultiValueEncoded::new();
+
+        for (proposal_id, proposal) in self.proposals().iter() {
+            result.push((proposal_id, proposal, self.get_approval_count(proposal_id)).into());
+        }
+
+        result
+    }
+
+    // -----------------------
+    // storage
+    // -----------------------
+
+    #[view(getSigners)]
+    #[storage_mapper("multisigSigners")]
+    fn signers(&self) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[view(getThreshold)]
+    #[storage_mapper("multisigThreshold")]
+    fn threshold(&self) -> SingleValueMapper<u32>;
+
+    #[storage_mapper("multisigLastProposalId")]
+    fn last_proposal_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("multisigProposals")]
+    fn proposals(&self) -> MapMapper<u64, Proposal<Self::Api>>;
+
+    #[view(getApprovals)]
+    #[storage_mapper("multisigApprovals")]
+    fn approvals(&self, proposal_id: u64) -> UnorderedSetMapper<ManagedAddress>;
+
+    #[storage_mapper("multisigReservedFunds")]
+    fn reserved_funds(&self, token: &Option<TokenIdentifier>) -> SingleValueMapper<BigUint>;
+
+    #[view(getReservesPending)]
+    #[storage_mapper("multisigReservesPending")]
+    fn reserves_pending(&self) -> SingleValueMapper<bool>;
+
+    // -----------------------
+    // events
+    // -----------------------
+
+    #[event("proposalCreated")]
+    fn proposal_created_event(
+        &self,
+        #[indexed] proposal_id: u64,
+        #[indexed] proposer: &ManagedAddress,
+        action: &MultisigAction<Self::Api>,
+    );
+
+    #[event("proposalApproved")]
+    fn proposal_approved_event(&self, #[indexed] proposal_id: u64, #[indexed] signer: &ManagedAddress);
+
+    #[event("proposalUnapproved")]
+    fn proposal_unapproved_event(
+        &self,
+        #[indexed] proposal_id: u64,
+        #[indexed] signer: &ManagedAddress,
+    );
+
+    #[event("proposalExecuted")]
+    fn proposal_executed_event(&self, #[indexed] proposal_id: u64);
+
+    #[event("proposalDiscarded")]
+    fn proposal_discarded_event(&self, #[indexed] proposal_id: u64);
+}
@@ -0,0 +1,153 @@
+elrond_wasm::imports!();
+elrond_wasm::derive_imports!();
+
+use crate::Role;
+
+pub const DEFAULT_TIMELOCK_DELAY: u64 = 86_400;
+pub const MIN_TIMELOCK_DELAY: u64 = 3_600;
+
+// contract actions are kept encoded, each contract decodes its own type
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub enum QueuedAction<M: ManagedTypeApi> {
+    SetTimelockDelay(u64),
+    Contract(ManagedBuffer<M>),
+}
+
+#[derive(TypeAbi, TopEncode, TopDecode, NestedEncode, NestedDecode, PartialEq, Eq, Clone)]
+pub struct PendingAction<M: ManagedTypeApi> {
+    pub action: QueuedAction<M>,
+    pub execute_after: u64,
+}
+
+// Delay for sensitive admin overrides. Contracts queue their own actions
+// with queue_action and apply what take_ready_action hands back from their
+// executeAction endpoint.
+#[elrond_wasm::module]
+pub trait TimelockModule: crate::RolesModule {
+    // -----------------------
+    // timelock methods
+    // -----------------------
+
+    // overrides are always timelocked, also for contracts deployed before
+    fn init_timelock(&self) {
+        self.timelock_delay().set_if_empty(&DEFAULT_TIMELOCK_DELAY);
+    }
+
+    // raising the delay is always safe, lowering it goes through the queue
+    #[endpoint(setTimelockDelay)]
+    fn set_timelock_delay(&self, delay: u64) {
+        self.require_role(Role::Admin);
+
+        require!(delay >= MIN_TIMELOCK_DELAY, "Timelock delay is too short!");
+
+        if delay >= self.timelock_delay().get() {
+            self.timelock_delay().set(&delay);
+        } else {
+            self.queue(QueuedAction::SetTimelockDelay(delay));
+        }
+    }
+
+    #[endpoint(cancelAction)]
+    fn cancel_action(&self, action_id: u64) {
+        self.require_role(Role::Admin);
+
+        require!(
+            self.pending_actions().remove(&action_id).is_some(),
+            "Action not found!"
+        );
+
+        self.action_cancelled_event(action_id);
+    }
+
+    fn queue_action<T: elrond_codec::TopEncode>(&self, action: &T) {
+        let encoded = self.serializer().top_encode_to_managed_buffer(action);
+
+        self.queue(QueuedAction::Contract(encoded));
+    }
+
+    fn queue(&self, action: QueuedAction<Self::Api>) {
+        let delay = self.timelock_delay().get();
+
+        let action_id = self.last_action_id().get() + 1;
+        self.last_action_id().set(&action_id);
+
+        let execute_after = self.blockchain().get_block_timestamp() + delay;
+        let pending = PendingAction {
+            action,
+            execute_after,
+        };
+
+        self.action_queued_event(action_id, execute_after, &pending.action);
+        self.pending_actions().insert(action_id, pending);
+    }
+
+    // removes the action once its delay passed; delay changes are applied
+    // here, contract actions are handed back for the contract to apply
+    fn take_ready_action<T: elrond_codec::TopDecode>(&self, action_id: u64) -> Option<T> {
+        let pending = self.pending_actions().get(&action_id);
+        require!(pending.is_some(), "Action not found!");
+
+        let pending = pending.unwrap();
+        require!(
+            self.blockchain().get_block_timestamp() >= pending.execute_after,
+            "Action is still timelocked!"
+        );
+
+        self.pending_actions().remove(&action_id);
+        self.action_executed_event(action_id);
+
+        match pending.action {
+            QueuedAction::SetTimelockDelay(delay) => {
+                self.timelock_delay().set(&delay);
+
+                None
+            }
+            QueuedAction::Contract(action) => {
+                Some(self.serializer().top_decode_from_managed_buffer(&action))
+            }
+        }
+    }
+
+    #[view(getPendingActions)]
+    fn get_pending_actions(&self) -> MultiValueEncoded<MultiValue2<u64, PendingAction<Self::Api>>> {
+        let mut result = MultiValueEncoded::new();
+
+        for (action_id, pending) in self.pending_actions().iter() {
+            result.push((action_id, pending).into());
+        }
+
+        result
+    }
+
+    // -----------------------
+    // storage
+    // -----------------------
+
+    #[view(getTimelockDelay)]
+    #[storage_mapper("timelockDelay")]
+    fn timelock_delay(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("timelockLastActionId")]
+    fn last_action_id(&self) -> SingleValueMapper<u64>;
+
+    #[storage_mapper("timelockPendingActions")]
+    fn pending_actions(&self) -> MapMapper<u64, PendingAction<Self::Api>>;
+
+    // -----------------------
+    // events
+    // -----------------------
+
+    #[event("actionQueued")]
+    fn action_queued_event(
+        &self,
+        #[indexed] action_id: u64,
+        #[indexed] execute_after: u64,
+        action: &QueuedAction<Self::Api>,
+    );
+
+    #[event("actionExecuted")]
+    fn action_executed_event(&self, #[indexed] action_id: u64);
+
+    #[event("actionCancelled")]
+    fn action_cancelled_event(&self, #[indexed] action_id: u64);
+}